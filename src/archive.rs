@@ -0,0 +1,484 @@
+use crate::{
+	BasicTarError, Header, RecordType, record,
+	header::raw::{ pax, BLOCK_LEN, LINKNAME_LEN, NAME_LEN, TypeFlag },
+	helpers::U64Ext
+};
+use std::{
+	io::{ self, Read, Write },
+	time::SystemTime
+};
+
+
+/// An iterator over the records in an archive stream
+///
+/// GNU long name and long link records are consumed transparently and their payloads are applied
+/// as path or link name to the following record. Pax records are consumed too by default and their
+/// `path`, `linkpath`, `size` and `mtime` keys override the fields of the following record (or of
+/// all subsequent records for global pax records).
+///
+/// _Note: the iterator stops after the end of archive indicator (two empty headers) or after the
+/// first error; any data after the end of archive indicator is not read_
+pub struct ArchiveReader<R: Read> {
+	reader: R,
+	long_name: Option<String>,
+	long_linkname: Option<String>,
+	apply_pax: bool,
+	strict_typeflags: bool,
+	pax_global: Vec<(String, String)>,
+	pax_single: Vec<(String, String)>,
+	total_len: Option<u64>,
+	offset: u64,
+	empty_headers: usize,
+	done: bool
+}
+impl<R: Read> ArchiveReader<R> {
+	/// Creates a new archive reader over `reader`
+	pub fn new(reader: R) -> Self {
+		Self {
+			reader, long_name: None, long_linkname: None,
+			apply_pax: true, strict_typeflags: false,
+			pax_global: Vec::new(), pax_single: Vec::new(),
+			total_len: None, offset: 0, empty_headers: 0, done: false
+		}
+	}
+	/// Sets whether pax records are applied to the following records (default) or are returned
+	/// as raw records
+	pub fn pax_overrides(mut self, apply: bool) -> Self {
+		self.apply_pax = apply;
+		self
+	}
+	/// Sets whether records with a type flag that is not a regular file, link, device, directory,
+	/// FIFO or pax record (e.g. GNU sparse files) are rejected with `BasicTarError::Unsupported`
+	/// instead of being returned as they are (default)
+	pub fn strict_typeflags(mut self, strict: bool) -> Self {
+		self.strict_typeflags = strict;
+		self
+	}
+	/// Sets the total length of the archive (e.g. the file length) so that records whose payload
+	/// exceeds the archive are rejected before the payload buffer is allocated
+	pub fn with_total_len(mut self, total_len: u64) -> Self {
+		self.total_len = Some(total_len);
+		self
+	}
+	
+	/// Decodes the NUL-terminated payload of a GNU long name or long link record
+	fn gnu_long_field(mut payload: Vec<u8>) -> Result<String, BasicTarError> {
+		let nul = payload.iter().position(|b| *b == 0x00).unwrap_or(payload.len());
+		payload.truncate(nul);
+		String::from_utf8(payload)
+			.map_err(|_| BasicTarError::Unsupported("GNU long name or link is not UTF-8"))
+	}
+	
+	/// Parses the pax record `payload` and returns the key/value pairs that are applied to the
+	/// following records
+	///
+	/// _Note: only the applied keys must be UTF-8, so other keys may have binary values (e.g.
+	/// xattrs)_
+	fn pax_overrides_from(payload: &[u8]) -> Result<Vec<(String, String)>, BasicTarError> {
+		let mut pairs = Vec::new();
+		for (key, value) in pax::parse_bytes(payload)? {
+			if let "path" | "linkpath" | "size" | "mtime" = key.as_str() {
+				let value = String::from_utf8(value)
+					.map_err(|_| BasicTarError::InvalidData("Pax record is not UTF-8"))?;
+				pairs.push((key, value));
+			}
+		}
+		Ok(pairs)
+	}
+	/// Applies the pax key/value `pairs` to `header`
+	fn apply_pax(header: &mut Header, pairs: &[(String, String)]) -> Result<(), BasicTarError> {
+		header.size = header.effective_size(pairs)?;
+		for (key, value) in pairs {
+			match key.as_str() {
+				"path" => header.path = value.clone(),
+				"linkpath" => header.linkname = Some(value.clone()),
+				"mtime" => {
+					let seconds = value.split('.').next().unwrap_or_default();
+					header.mtime = Some(seconds.parse()
+						.map_err(|_| BasicTarError::InvalidData("Invalid pax modification time"))?);
+				},
+				_ => ()
+			}
+		}
+		Ok(())
+	}
+	
+	/// Rejects `typeflag` if it is not a standard type flag
+	fn check_typeflag(typeflag: u8) -> Result<(), BasicTarError> {
+		match RecordType::from(typeflag) {
+			RecordType::Other(TypeFlag::REGULAR_LEGACY) => Ok(()),
+			RecordType::Other(TypeFlag::RESERVED) =>
+				Err(BasicTarError::Unsupported("Unsupported type flag: contiguous file")),
+			RecordType::Other(TypeFlag::GNU_SPARSE) =>
+				Err(BasicTarError::Unsupported("Unsupported type flag: GNU sparse file")),
+			RecordType::Other(TypeFlag::GNU_DUMPDIR) =>
+				Err(BasicTarError::Unsupported("Unsupported type flag: GNU dumpdir")),
+			RecordType::Other(TypeFlag::GNU_VOLHDR) =>
+				Err(BasicTarError::Unsupported("Unsupported type flag: GNU volume header")),
+			RecordType::Other(TypeFlag::GNU_MULTIVOL) => Err(BasicTarError::Unsupported(
+				"Unsupported type flag: GNU multi-volume continuation"
+			)),
+			RecordType::Other(_) =>
+				Err(BasicTarError::Unsupported("Unsupported type flag: unknown")),
+			_ => Ok(())
+		}
+	}
+	
+	/// Reads the next header and tracks the offset
+	fn read_header(&mut self) -> Result<Option<Header>, BasicTarError> {
+		let header = record::read_header(&mut self.reader)?;
+		self.offset += BLOCK_LEN as u64;
+		Ok(header)
+	}
+	/// Reads the payload of `header` after checking it against the total length if any
+	fn read_payload(&mut self, header: &Header) -> Result<Vec<u8>, BasicTarError> {
+		match self.total_len {
+			Some(total_len) if self.offset.saturating_add(header.size) > total_len =>
+				Err(BasicTarError::InvalidData("Record size exceeds the archive length"))?,
+			_ => ()
+		}
+		
+		let payload = record::read_payload(&mut self.reader, header)?;
+		self.offset += header.size.ceil_to_multiple_of(BLOCK_LEN as u64);
+		Ok(payload)
+	}
+	
+	/// Reads the next header and applies the preceding GNU long name, long link and pax records if
+	/// any
+	fn read_next_header(&mut self) -> Result<Option<Header>, BasicTarError> {
+		loop {
+			let mut header = match self.read_header()? {
+				Some(header) => header,
+				None => return Ok(None)
+			};
+			match header.typeflag {
+				TypeFlag::GNU_LONGNAME => {
+					let payload = self.read_payload(&header)?;
+					self.long_name = Some(Self::gnu_long_field(payload)?)
+				},
+				TypeFlag::GNU_LONGLINK => {
+					let payload = self.read_payload(&header)?;
+					self.long_linkname = Some(Self::gnu_long_field(payload)?)
+				},
+				TypeFlag::PAX_SINGLE if self.apply_pax => {
+					let payload = self.read_payload(&header)?;
+					self.pax_single.extend(Self::pax_overrides_from(&payload)?)
+				},
+				TypeFlag::PAX_GLOBAL if self.apply_pax => {
+					let payload = self.read_payload(&header)?;
+					self.pax_global.extend(Self::pax_overrides_from(&payload)?)
+				},
+				_ => {
+					// Apply the overrides before reading the payload since they may change the size
+					if let Some(long_name) = self.long_name.take() {
+						header.path = long_name;
+					}
+					if let Some(long_linkname) = self.long_linkname.take() {
+						header.linkname = Some(long_linkname);
+					}
+					Self::apply_pax(&mut header, &self.pax_global)?;
+					Self::apply_pax(&mut header, &self.pax_single)?;
+					self.pax_single.clear();
+					
+					if self.strict_typeflags {
+						Self::check_typeflag(header.typeflag)?;
+					}
+					return Ok(Some(header))
+				}
+			}
+		}
+	}
+	
+	/// Reads the next record whose path matches `predicate` and skips the payloads of all other
+	/// records without allocating a buffer
+	fn read_next_filtered(&mut self, predicate: &mut impl FnMut(&str) -> bool)
+		-> Result<Option<(Header, Vec<u8>)>, BasicTarError>
+	{
+		loop {
+			let header = match self.read_next_header()? {
+				Some(header) => header,
+				None => return Ok(None)
+			};
+			if predicate(&header.path) {
+				let payload = self.read_payload(&header)?;
+				return Ok(Some((header, payload)))
+			}
+			
+			record::skip_record(&mut self.reader, &header)?;
+			self.offset += header.size.ceil_to_multiple_of(BLOCK_LEN as u64);
+			self.empty_headers = 0;
+		}
+	}
+	/// Returns the next record whose path matches `predicate` (see `Iterator::next`)
+	fn next_filtered(&mut self, predicate: &mut impl FnMut(&str) -> bool)
+		-> Option<Result<(Header, Vec<u8>), BasicTarError>>
+	{
+		while !self.done {
+			match self.read_next_filtered(predicate) {
+				Ok(Some(record)) => {
+					self.empty_headers = 0;
+					return Some(Ok(record))
+				},
+				Ok(None) => {
+					self.empty_headers += 1;
+					self.done = self.empty_headers >= 2;
+				},
+				Err(BasicTarError::Io(io::ErrorKind::UnexpectedEof)) if self.empty_headers > 0 =>
+					self.done = true,
+				Err(e) => {
+					self.done = true;
+					return Some(Err(e))
+				}
+			}
+		}
+		None
+	}
+	
+	/// Returns an iterator over the records whose path matches `predicate`; the payloads of all
+	/// other records are skipped without allocating a buffer
+	pub fn filter_paths<P: FnMut(&str) -> bool>(self, predicate: P) -> FilterPaths<R, P> {
+		FilterPaths{ reader: self, predicate }
+	}
+	
+	/// Returns the underlying reader
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+}
+impl<R: Read> Iterator for ArchiveReader<R> {
+	type Item = Result<(Header, Vec<u8>), BasicTarError>;
+	
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_filtered(&mut |_| true)
+	}
+}
+
+
+/// An iterator over the records in an archive stream whose path matches a predicate (see
+/// `ArchiveReader::filter_paths`)
+pub struct FilterPaths<R: Read, P: FnMut(&str) -> bool> {
+	reader: ArchiveReader<R>,
+	predicate: P
+}
+impl<R: Read, P: FnMut(&str) -> bool> FilterPaths<R, P> {
+	/// Returns the underlying archive reader
+	pub fn into_inner(self) -> ArchiveReader<R> {
+		self.reader
+	}
+}
+impl<R: Read, P: FnMut(&str) -> bool> Iterator for FilterPaths<R, P> {
+	type Item = Result<(Header, Vec<u8>), BasicTarError>;
+	
+	fn next(&mut self) -> Option<Self::Item> {
+		self.reader.next_filtered(&mut self.predicate)
+	}
+}
+
+
+/// Parses all records of the in-memory archive `data` until the end of archive indicator
+///
+/// _Note: unlike `ArchiveReader`, GNU long name and pax records are returned as they are; a single
+/// empty header at the end of `data` is accepted as end of archive indicator too_
+pub fn parse_archive(data: &[u8]) -> Result<Vec<(Header, Vec<u8>)>, BasicTarError> {
+	if data.len() % BLOCK_LEN != 0 {
+		Err(BasicTarError::InvalidData("Archive length is not a multiple of the block size"))?
+	}
+	
+	let (mut records, mut pos, mut empty_headers) = (Vec::new(), 0, 0);
+	while empty_headers < 2 {
+		// Parse the next header
+		let header = match data.get(pos..pos + BLOCK_LEN) {
+			Some(block) => Header::parse_slice(block),
+			None if empty_headers > 0 => break,
+			None => Err(BasicTarError::InvalidData("Archive is truncated"))?
+		};
+		pos += BLOCK_LEN;
+		let header = match header {
+			Err(BasicTarError::EmptyHeader) => {
+				empty_headers += 1;
+				continue
+			},
+			header => header?
+		};
+		
+		// Get the payload and skip the padding
+		empty_headers = 0;
+		let payload_len = header.payload_len_checked()?;
+		let expected = header.size.ceil_to_multiple_of(BLOCK_LEN as u64);
+		let payload = data[pos..].get(..payload_len)
+			.ok_or(BasicTarError::Truncated{ expected, got: (data.len() - pos) as u64 })?;
+		pos += expected as usize;
+		records.push((header, payload.to_vec()));
+	}
+	Ok(records)
+}
+
+
+/// Walks all records in `stream` until the end of archive indicator, verifies their header
+/// checksums and payload lengths and returns the amount of records
+///
+/// _Note: the payloads are drained without allocating a buffer; GNU long name and pax records are
+/// counted as records too_
+pub fn verify_archive(stream: impl Read) -> Result<usize, BasicTarError> {
+	let mut records = 0;
+	walk_archive(stream, |_, _| records += 1)?;
+	Ok(records)
+}
+
+
+/// The position of a record within an archive
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecordIndex {
+	/// The offset of the record's header
+	pub header_offset: u64,
+	/// The offset of the record's payload
+	pub payload_offset: u64,
+	/// The record's header
+	pub header: Header
+}
+/// Walks all records in `stream` until the end of archive indicator and returns their positions
+/// (e.g. to build an index for random access)
+///
+/// _Note: the payloads are drained without allocating a buffer; GNU long name and pax records are
+/// indexed as they are_
+pub fn index_archive(stream: impl Read) -> Result<Vec<RecordIndex>, BasicTarError> {
+	let mut index = Vec::new();
+	walk_archive(stream, |header_offset, header| {
+		let payload_offset = header_offset + BLOCK_LEN as u64;
+		index.push(RecordIndex{ header_offset, payload_offset, header })
+	})?;
+	Ok(index)
+}
+
+
+/// Walks all records in `stream` until the end of archive indicator, skips their payloads and
+/// calls `record_cb` with the header offset and the header of every record
+fn walk_archive(mut stream: impl Read, mut record_cb: impl FnMut(u64, Header))
+	-> Result<(), BasicTarError>
+{
+	let (mut offset, mut empty_headers) = (0, 0);
+	while empty_headers < 2 {
+		match record::read_header(&mut stream) {
+			Ok(Some(header)) => {
+				record::skip_record(&mut stream, &header)?;
+				let record_len = header.record_len()?;
+				record_cb(offset, header);
+				offset += record_len;
+				empty_headers = 0;
+			},
+			Ok(None) => {
+				offset += BLOCK_LEN as u64;
+				empty_headers += 1
+			},
+			Err(BasicTarError::Io(io::ErrorKind::UnexpectedEof)) if empty_headers > 0 => break,
+			Err(e) => Err(e)?
+		}
+	}
+	Ok(())
+}
+
+
+/// A writer that appends records to an archive stream
+///
+/// _Note: you must call `finish` to write the end of archive indicator (two empty headers)_
+pub struct ArchiveWriter<W: Write> {
+	writer: W,
+	gnu_long_names: bool,
+	mtime: Option<u64>,
+	written: u64
+}
+impl<W: Write> ArchiveWriter<W> {
+	/// Creates a new archive writer over `writer`
+	pub fn new(writer: W) -> Self {
+		Self{ writer, gnu_long_names: false, mtime: None, written: 0 }
+	}
+	/// Sets whether paths and link names that are longer than 100 bytes are written as GNU long
+	/// name and long link records (like GNU tar does) instead of using the USTAR prefix
+	pub fn gnu_long_names(mut self, enable: bool) -> Self {
+		self.gnu_long_names = enable;
+		self
+	}
+	/// Sets a fixed modification time for records appended with `append_data` instead of the
+	/// current time (e.g. to create reproducible archives)
+	pub fn mtime(mut self, mtime: u64) -> Self {
+		self.mtime = Some(mtime);
+		self
+	}
+	
+	/// Writes `header`, `payload` and the padding to the underlying writer
+	///
+	/// _Note: if GNU long names are enabled, a long path or link name is written as preceding GNU
+	/// record and truncated to 100 bytes in `header`_
+	pub fn append(&mut self, mut header: Header, payload: &[u8]) -> Result<(), BasicTarError> {
+		// Write the long link before the long name like GNU tar does
+		if let Some(linkname) = header.linkname.as_mut().filter(|_| self.gnu_long_names) {
+			if linkname.len() > LINKNAME_LEN {
+				self.append_gnu_long_field(TypeFlag::GNU_LONGLINK, linkname)?;
+				Self::truncate(linkname, LINKNAME_LEN);
+			}
+		}
+		if self.gnu_long_names && header.path.len() > NAME_LEN {
+			self.append_gnu_long_field(TypeFlag::GNU_LONGNAME, &header.path)?;
+			Self::truncate(&mut header.path, NAME_LEN);
+		}
+		
+		record::write_record(header, payload, &mut self.writer)?;
+		let payload_total_len = (payload.len() as u64).ceil_to_multiple_of(BLOCK_LEN as u64);
+		self.written += BLOCK_LEN as u64 + payload_total_len;
+		Ok(())
+	}
+	
+	/// Writes `data` as regular file with path `path`, mode 0o644 and UID/GID 0 to the underlying
+	/// writer
+	///
+	/// _Note: the modification time is the current time unless a fixed time is set with `mtime`_
+	pub fn append_data(&mut self, path: &str, data: &[u8]) -> Result<(), BasicTarError> {
+		let mut header = Header::builder(path).mode(0o644).uid(0).gid(0)
+			.size(data.len() as u64).build();
+		match self.mtime {
+			Some(mtime) => header.mtime = Some(mtime),
+			None => header.set_mtime_from_system_time(SystemTime::now())?
+		}
+		self.append(header, data)
+	}
+	
+	/// Writes a GNU long name or long link record with the NUL-terminated `value` as payload
+	fn append_gnu_long_field(&mut self, typeflag: u8, value: &str) -> Result<(), BasicTarError> {
+		let mut payload = value.as_bytes().to_vec();
+		payload.push(0);
+		let header = Header {
+			uname: Some("root".into()), gname: Some("root".into()),
+			magic: Some(*b"ustar "), version: Some(*b" \0"),
+			..Header::builder("././@LongLink").mode(0o644).uid(0).gid(0)
+				.size(payload.len() as u64).mtime(0).typeflag(typeflag).build()
+		};
+		self.append(header, &payload)
+	}
+	/// Truncates `string` to at most `len` bytes at a char boundary
+	fn truncate(string: &mut String, len: usize) {
+		let boundary = (0..=len).rev().find(|idx| string.is_char_boundary(*idx));
+		string.truncate(boundary.unwrap_or_default())
+	}
+	
+	/// The amount of bytes written so far
+	pub fn written(&self) -> u64 {
+		self.written
+	}
+	
+	/// Writes the end of archive indicator and returns the underlying writer
+	pub fn finish(mut self) -> Result<W, BasicTarError> {
+		record::write_eof(&mut self.writer)?;
+		Ok(self.writer)
+	}
+	/// Writes the end of archive indicator, pads the archive with zero blocks to a multiple of
+	/// `blocking_factor` blocks (like GNU tar does) and returns the underlying writer
+	///
+	/// _Note: see `align_archive_len`_
+	pub fn finish_aligned(mut self, blocking_factor: usize) -> Result<W, BasicTarError> {
+		let eof_len = self.written + 2 * BLOCK_LEN as u64;
+		let aligned_len = record::align_archive_len(eof_len, blocking_factor);
+		let blocks = (aligned_len - self.written) / BLOCK_LEN as u64;
+		record::write_trailer(&mut self.writer, blocks as usize)?;
+		Ok(self.writer)
+	}
+}
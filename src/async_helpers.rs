@@ -0,0 +1,109 @@
+use std::{
+	cmp::min,
+	future::Future,
+	io::{
+		self,
+		ErrorKind::{ Interrupted, UnexpectedEof, WriteZero }
+	}
+};
+use tokio::io::{ AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _ };
+
+
+/// An extension for `tokio::io::AsyncRead`
+pub trait AsyncReadExt {
+	/// Tries to fill `buf` completely and calls the position callback `pos_cb` with the amount of
+	/// bytes read on *every* successful `read` call
+	///
+	/// _Note: This function behaves like `ReadExt::try_read_exact`, except that it is async_
+	fn try_read_exact<'a>(&'a mut self, buf: &'a mut[u8], pos_cb: impl FnMut(usize) + Send + 'a)
+		-> impl Future<Output = Result<(), io::Error>> + Send + 'a;
+	
+	/// Tries to consume `len` bytes and calls the position callback `pos_cb` with the amount of
+	/// bytes drained on *every* successful `read` call
+	///
+	/// _Note: This function behaves like `ReadExt::try_drain`, except that it is async_
+	fn try_drain<'a>(&'a mut self, len: usize, pos_cb: impl FnMut(usize) + Send + 'a)
+		-> impl Future<Output = Result<(), io::Error>> + Send + 'a;
+}
+impl<T: AsyncRead + Unpin + Send> AsyncReadExt for T {
+	async fn try_read_exact<'a>(&'a mut self, mut buf: &'a mut[u8],
+		mut pos_cb: impl FnMut(usize) + Send + 'a) -> Result<(), io::Error>
+	{
+		'read_loop: while !buf.is_empty() {
+			match self.read(buf).await {
+				Err(ref e) if e.kind() == Interrupted => continue 'read_loop,
+				Err(e) => Err(e)?,
+				Ok(0) => Err(io::Error::from(UnexpectedEof))?,
+				Ok(len) => {
+					buf = &mut buf[len..];
+					pos_cb(len)
+				}
+			}
+		}
+		Ok(())
+	}
+	async fn try_drain<'a>(&'a mut self, mut len: usize, mut pos_cb: impl FnMut(usize) + Send + 'a)
+		-> Result<(), io::Error>
+	{
+		// Read len bytes
+		while len > 0 {
+			// Create buffer and fill it
+			let buf = &mut[0; 4096][.. min(len, 4096)];
+			self.try_read_exact(buf, |read| {
+				len -= read;
+				pos_cb(read)
+			}).await?
+		}
+		Ok(())
+	}
+}
+
+
+/// An extension for `tokio::io::AsyncWrite`
+pub trait AsyncWriteExt {
+	/// Tries to write `data` completely and calls the position callback `pos_cb` with the amount of
+	/// bytes written on *every* successful `write` call
+	///
+	/// _Note: This function behaves like `WriteExt::try_write_exact`, except that it is async_
+	fn try_write_exact<'a>(&'a mut self, data: &'a [u8], pos_cb: impl FnMut(usize) + Send + 'a)
+		-> impl Future<Output = Result<(), io::Error>> + Send + 'a;
+	
+	/// Tries to write `len` zero bytes and calls the position callback `pos_cb` with the amount of
+	/// bytes written on *every* successful `write` call
+	///
+	/// _Note: This function behaves like `WriteExt::try_fill`, except that it is async_
+	fn try_fill<'a>(&'a mut self, len: usize, pos_cb: impl FnMut(usize) + Send + 'a)
+		-> impl Future<Output = Result<(), io::Error>> + Send + 'a;
+}
+impl<T: AsyncWrite + Unpin + Send> AsyncWriteExt for T {
+	async fn try_write_exact<'a>(&'a mut self, mut data: &'a [u8],
+		mut pos_cb: impl FnMut(usize) + Send + 'a) -> Result<(), io::Error>
+	{
+		'write_loop: while !data.is_empty() {
+			match self.write(data).await {
+				Err(ref e) if e.kind() == Interrupted => continue 'write_loop,
+				Err(e) => Err(e)?,
+				Ok(0) => Err(io::Error::from(WriteZero))?,
+				Ok(len) => {
+					data = &data[len..];
+					pos_cb(len);
+				}
+			}
+		}
+		Ok(())
+	}
+	async fn try_fill<'a>(&'a mut self, mut len: usize, mut pos_cb: impl FnMut(usize) + Send + 'a)
+		-> Result<(), io::Error>
+	{
+		// Write len zero bytes
+		while len > 0 {
+			// Create buffer and fill it
+			let buf = &mut[0; 4096][.. min(len, 4096)];
+			self.try_write_exact(buf, |written| {
+				len -= written;
+				pos_cb(written)
+			}).await?
+		}
+		Ok(())
+	}
+}
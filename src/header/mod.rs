@@ -1,13 +1,116 @@
 pub mod raw;
+mod mode;
 
+pub use self::mode::Mode;
 use crate::{
 	BasicTarError,
-	header::raw::{ StringExt, U64Ext, Checksum }
+	header::raw::{ StrExt, StringExt, U64Ext, Checksum, TypeFlag }
+};
+use alloc::{ format, string::String, vec::Vec };
+use core::{
+	cmp::max,
+	convert::TryFrom,
+	fmt::{ self, Display, Formatter },
+	mem
+};
+#[cfg(feature = "std")]
+use crate::helpers::{ ReadExt, WriteExt };
+#[cfg(feature = "std")]
+use std::{
+	fs,
+	io::{ self, Read, Write },
+	path::Path,
+	time::{ Duration, SystemTime, UNIX_EPOCH }
 };
 
 
+/// A typed representation of the record type flag
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordType {
+	/// A regular file
+	Regular,
+	/// A hardlink
+	HardLink,
+	/// A symlink
+	SymLink,
+	/// A character device
+	CharDevice,
+	/// A block device
+	BlockDevice,
+	/// A directory
+	Directory,
+	/// A FIFO-node (named pipe)
+	Fifo,
+	/// A pax interchange record that only affects the next file
+	PaxSingle,
+	/// A pax interchange record that affects all subsequent files
+	PaxGlobal,
+	/// Any other (unknown or implementation specific) type flag
+	Other(u8)
+}
+impl From<u8> for RecordType {
+	fn from(typeflag: u8) -> Self {
+		match typeflag {
+			TypeFlag::REGULAR => RecordType::Regular,
+			TypeFlag::HARDLINK => RecordType::HardLink,
+			TypeFlag::SYMLINK => RecordType::SymLink,
+			TypeFlag::CHAR_DEV => RecordType::CharDevice,
+			TypeFlag::BLOCK_DEV => RecordType::BlockDevice,
+			TypeFlag::DIRECTORY => RecordType::Directory,
+			TypeFlag::FIFO_NODE => RecordType::Fifo,
+			TypeFlag::PAX_SINGLE => RecordType::PaxSingle,
+			TypeFlag::PAX_GLOBAL => RecordType::PaxGlobal,
+			other => RecordType::Other(other)
+		}
+	}
+}
+impl From<RecordType> for u8 {
+	fn from(record_type: RecordType) -> Self {
+		match record_type {
+			RecordType::Regular => TypeFlag::REGULAR,
+			RecordType::HardLink => TypeFlag::HARDLINK,
+			RecordType::SymLink => TypeFlag::SYMLINK,
+			RecordType::CharDevice => TypeFlag::CHAR_DEV,
+			RecordType::BlockDevice => TypeFlag::BLOCK_DEV,
+			RecordType::Directory => TypeFlag::DIRECTORY,
+			RecordType::Fifo => TypeFlag::FIFO_NODE,
+			RecordType::PaxSingle => TypeFlag::PAX_SINGLE,
+			RecordType::PaxGlobal => TypeFlag::PAX_GLOBAL,
+			RecordType::Other(other) => other
+		}
+	}
+}
+
+impl Display for RecordType {
+	/// Formats the record type as human-readable description (e.g. `symbolic link`)
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let typeflag = u8::from(*self);
+		match TypeFlag::all().iter().find(|(flag, _)| *flag == typeflag) {
+			Some((_, name)) => write!(f, "{}", name),
+			None => write!(f, "unknown (0x{:02x})", typeflag)
+		}
+	}
+}
+
+/// The tar dialect of a header
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+	/// A classic old-style header without magic
+	Classic,
+	/// A POSIX USTAR header (magic `b"ustar\0"` and version `b"00"`)
+	Ustar,
+	/// A GNU header (magic `b"ustar "` and version `b" \0"`)
+	Gnu,
+	/// A POSIX USTAR header of a pax extended header record
+	Pax
+}
+
+
 /// A tar header
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
 	/// The record's path and name
 	pub path: String,
@@ -22,60 +125,682 @@ pub struct Header {
 	/// The record's modification time
 	pub mtime: Option<u64>,
 	/// The record's type
+	///
+	/// _Note: historic archives use `TypeFlag::REGULAR_LEGACY` for regular files and directories;
+	/// use the `is_*` predicates or `normalize_typeflag` to handle them_
 	pub typeflag: u8,
 	/// The record's link name
-	pub linkname: Option<String>
+	pub linkname: Option<String>,
+	/// The record's owner user name
+	pub uname: Option<String>,
+	/// The record's owner group name
+	pub gname: Option<String>,
+	/// The USTAR magic (usually `b"ustar\0"`) or `None` for classic archives
+	pub magic: Option<[u8; 6]>,
+	/// The USTAR version (usually `b"00"`) or `None` for classic archives
+	pub version: Option<[u8; 2]>,
+	/// The device major number (only for character and block devices)
+	pub devmajor: Option<u64>,
+	/// The device minor number (only for character and block devices)
+	pub devminor: Option<u64>,
+	/// The record's access time (only for GNU archives)
+	///
+	/// _Note: GNU headers store the access and change time in place of the USTAR prefix, so they
+	/// are only parsed and serialized for `Format::Gnu` headers; if either time is set, the path
+	/// must fit into the name field (e.g. by using GNU long name records)_
+	pub atime: Option<u64>,
+	/// The record's change time (only for GNU archives; see `atime`)
+	pub ctime: Option<u64>
 }
 impl Header {
+	/// Creates a new builder for a regular file header with path `path`
+	pub fn builder(path: impl Into<String>) -> HeaderBuilder {
+		HeaderBuilder::new(path)
+	}
+	
+	/// Creates a header for the filesystem entry at `path` from its metadata
+	///
+	/// _Note: symlinks are not followed; the mode, UID and GID are only set on unix platforms_
+	#[cfg(feature = "std")]
+	pub fn from_path(path: &Path) -> Result<Self, io::Error> {
+		let metadata = fs::symlink_metadata(path)?;
+		let path_str = path.to_str()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path is not UTF-8"))?;
+		let mut header = Header::builder(path_str).build();
+		
+		// Set the type flag, size and link name
+		let file_type = metadata.file_type();
+		if file_type.is_file() {
+			header.size = metadata.len();
+		} else if file_type.is_dir() {
+			header.typeflag = TypeFlag::DIRECTORY;
+		} else if file_type.is_symlink() {
+			let linkname = fs::read_link(path)?.to_str().map(String::from)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Link is not UTF-8"))?;
+			header.typeflag = TypeFlag::SYMLINK;
+			header.linkname = Some(linkname);
+		} else {
+			Err(io::Error::new(io::ErrorKind::InvalidInput, "Unsupported file type"))?
+		}
+		
+		// Set the modification time
+		if let Ok(modified) = metadata.modified() {
+			let _ = header.set_mtime_from_system_time(modified);
+		}
+		
+		// Set the mode, UID and GID
+		#[cfg(unix)] {
+			use std::os::unix::fs::MetadataExt;
+			header.mode = Some(u64::from(metadata.mode()) & 0o7777);
+			header.uid = Some(u64::from(metadata.uid()));
+			header.gid = Some(u64::from(metadata.gid()));
+		}
+		Ok(header)
+	}
+	
+	/// Resets the header to an empty regular file header (like `Header::builder("")`) so that it
+	/// can be reused for the next record
+	///
+	/// _Note: the allocation of `path` is kept to avoid reallocations in tight loops_
+	pub fn reset(&mut self) {
+		let mut path = mem::take(&mut self.path);
+		path.clear();
+		*self = Header{ path, typeflag: TypeFlag::REGULAR, ..Default::default() };
+	}
+	
+	/// The record's type as typed representation of `typeflag`
+	pub fn record_type(&self) -> RecordType {
+		RecordType::from(self.typeflag)
+	}
+	
 	/// Parses a raw byte block into a classic tar header
-	pub fn parse(data: raw::header::Raw) -> Result<Self, BasicTarError> {
+	///
+	/// _Note: this function never panics; arbitrary input is either parsed or rejected with an
+	/// error, which makes it suitable as fuzzing target_
+	pub fn parse(data: &raw::header::Raw) -> Result<Self, BasicTarError> {
+		Self::parse_slice(data)
+	}
+	/// Parses a raw byte block into a classic tar header and replaces invalid UTF-8 sequences in
+	/// the path, link name, user name and group name with `U+FFFD`
+	///
+	/// _Note: this is useful for legacy archives with non-UTF-8 (e.g. Latin-1) names; however, the
+	/// original bytes are lost_
+	pub fn parse_lossy(data: &raw::header::Raw) -> Result<Self, BasicTarError> {
+		Self::parse_block(data, |field| Ok(Option::from_field_lossy(field)), true)
+	}
+	/// Parses a raw byte block into a classic tar header without verifying the checksum
+	///
+	/// _Warning: the checksum is the only integrity check of a header; without it, corrupted or
+	/// arbitrary data (e.g. a misaligned payload block) may be parsed as a seemingly valid header.
+	/// Use this only to salvage data from damaged archives and treat the result with care_
+	pub fn parse_ignore_checksum(data: &raw::header::Raw) -> Result<Self, BasicTarError> {
+		Self::parse_block(data, Option::from_field, false)
+	}
+	/// Parses the path (including the USTAR prefix if any) and the link name of a raw byte block
+	/// as raw bytes without UTF-8 validation
+	///
+	/// _Note: the fields are only NUL-trimmed, so any byte sequence (e.g. a non-UTF-8 unix path)
+	/// is retained_
+	pub fn parse_raw_fields(data: &raw::header::Raw)
+		-> Result<(Vec<u8>, Option<Vec<u8>>), BasicTarError>
+	{
+		// Check for an empty header and verify the checksum
+		if raw::is_empty_block(data) {
+			Err(BasicTarError::EmptyHeader)?
+		}
+		let tar = raw::header::Header::from(data);
+		Checksum::verify(&tar)?;
+		
+		// Join the prefix (only USTAR headers have one) and the name and get the link name
+		let mut path = match Self::has_prefix(&tar) {
+			true => tar.prefix_bytes().to_vec(),
+			false => Vec::new()
+		};
+		if !path.is_empty() {
+			path.push(b'/');
+		}
+		path.extend_from_slice(tar.name_bytes());
+		let linkname = Some(tar.linkname_bytes().to_vec()).filter(|linkname| !linkname.is_empty());
+		Ok((path, linkname))
+	}
+	/// Reads exactly one block from `stream` and parses it into a classic tar header
+	///
+	/// _Note: like `parse`, this function returns `BasicTarError::EmptyHeader` for an all-zero or
+	/// all-space block_
+	#[cfg(feature = "std")]
+	pub fn parse_from_reader(mut stream: impl Read) -> Result<Self, BasicTarError> {
+		let mut header_raw = raw::header::raw();
+		stream.try_read_exact(&mut header_raw, |_| {})?;
+		Self::parse(&header_raw)
+	}
+	/// Parses a borrowed raw byte block into a classic tar header
+	///
+	/// _Note: `data` must be exactly one block (512 bytes) long_
+	pub fn parse_slice(data: &[u8]) -> Result<Self, BasicTarError> {
+		Self::parse_block(data, Option::from_field, true)
+	}
+	/// Parses a borrowed raw byte block into a classic tar header, uses `decode` to decode the
+	/// string fields and verifies the checksum if `verify` is set
+	fn parse_block(data: &[u8], decode: fn(&[u8]) -> Result<Option<String>, BasicTarError>,
+		verify: bool) -> Result<Self, BasicTarError>
+	{
+		let data = <&raw::header::Raw>::try_from(data)
+			.map_err(|_| BasicTarError::ApiMisuse("`data` must be exactly one block long"))?;
+		
 		// Check if we have an empty header
-		if data.as_ref() == raw::header::raw().as_ref() {
+		if raw::is_empty_block(data) {
 			Err(BasicTarError::EmptyHeader)?
 		}
 		
 		// Parse the basic tar header and verify the checksum
 		let tar = raw::header::Header::from(data);
-		Checksum::verify(&tar)?;
+		if verify {
+			Checksum::verify(&tar)?;
+		}
 		
-		// Decode the path
-		let path = String::from_field(&tar.name)?;
+		// Decode the USTAR prefix or the access and change time of GNU headers
+		let prefix = match Self::has_prefix(&tar) {
+			true => decode(&tar.prefix)?,
+			false => None
+		};
+		let (atime, ctime) = Self::decode_gnu_times(&tar)?;
+		
+		// Decode the path and prepend the USTAR prefix if any
+		let name = decode(&tar.name)?.ok_or(BasicTarError::InvalidData("Required field is empty"))?;
+		let path = match prefix {
+			Some(prefix) => format!("{}/{}", prefix, name),
+			None => name
+		};
 		
 		// Decode the mode, UID and GID
 		let mode = Option::from_octal_field(&tar.mode)?;
 		let uid = Option::from_octal_field(&tar.uid)?;
 		let gid = Option::from_octal_field(&tar.gid)?;
 		
-		// Decode the size and the modification time
-		let size = u64::from_octal_field(&tar.size)?;
+		// Decode the size and the modification time (GNU volume headers leave the size empty)
+		let size = Option::from_octal_field(&tar.size)?.unwrap_or_default();
+		if size > raw::MAX_SIZE {
+			Err(BasicTarError::InvalidData("Size is too large"))?
+		}
 		let mtime = Option::from_octal_field(&tar.mtime)?;
 		
-		// Decode link name and create the struct
-		let linkname = Option::from_field(&tar.linkname)?;
-		Ok(Self{ path, mode, uid, gid, size, typeflag: tar.typeflag[0], mtime, linkname })
+		// Decode link name
+		let linkname = decode(&tar.linkname)?;
+		
+		// Decode the user and group names
+		let uname = decode(&tar.uname)?;
+		let gname = decode(&tar.gname)?;
+		
+		// Decode the device numbers (they are only meaningful for device records)
+		let (devmajor, devminor) = match tar.typeflag[0] {
+			TypeFlag::CHAR_DEV | TypeFlag::BLOCK_DEV => {
+				let devmajor = Option::from_octal_field(&tar.devmajor)?;
+				(devmajor, Option::from_octal_field(&tar.devminor)?)
+			},
+			_ => (None, None)
+		};
+		
+		// Decode the USTAR magic and version and create the struct
+		let magic = Some(tar.magic).filter(|magic| magic != &[0; 6]);
+		let version = Some(tar.version).filter(|version| version != &[0; 2]);
+		Ok(Self {
+			path, mode, uid, gid, size, mtime,
+			typeflag: tar.typeflag[0], linkname, uname, gname, magic, version,
+			devmajor, devminor, atime, ctime
+		})
+	}
+	
+	/// The header's format as detected from the magic, version and type flag
+	pub fn format(&self) -> Format {
+		match (self.magic.as_ref(), self.version.as_ref()) {
+			(Some(b"ustar\0"), Some(b"00")) => match self.typeflag {
+				TypeFlag::PAX_SINGLE | TypeFlag::PAX_GLOBAL => Format::Pax,
+				_ => Format::Ustar
+			},
+			(Some(b"ustar "), Some(b" \0")) => Format::Gnu,
+			_ => Format::Classic
+		}
+	}
+	
+	/// Validates that the record's path is safe to extract relative to a target directory
+	///
+	/// A path is considered safe if
+	///  - it does not contain NUL bytes
+	///  - it is not absolute (i.e. it does not start with `/` or `\` and does not start with a
+	///    Windows drive prefix like `C:`)
+	///  - it does not contain a `..` component (components are separated by `/` or `\`)
+	///
+	/// _Note: this does not validate the link name of symlinks or hardlinks_
+	pub fn validate_path(&self) -> Result<(), BasicTarError> {
+		if self.path.contains('\0') {
+			Err(BasicTarError::InvalidData("Path contains NUL bytes"))?
+		}
+		let drive_prefix = match self.path.as_bytes() {
+			[drive, b':', ..] => drive.is_ascii_alphabetic(),
+			_ => false
+		};
+		if self.path.starts_with('/') || self.path.starts_with('\\') || drive_prefix {
+			Err(BasicTarError::InvalidData("Path is absolute"))?
+		}
+		if self.path.split(['/', '\\']).any(|component| component == "..") {
+			Err(BasicTarError::InvalidData("Path contains a parent directory component"))?
+		}
+		Ok(())
+	}
+	/// Whether the record's path is safe to extract (see `validate_path` for the exact rules)
+	pub fn is_path_safe(&self) -> bool {
+		self.validate_path().is_ok()
+	}
+	/// The record's path without `.` components and redundant separators (e.g. `./dir//file`
+	/// becomes `dir/file`) to compare it against expected paths
+	///
+	/// _Note: the stored `path` is not modified; a leading and a trailing `/` are preserved_
+	pub fn canonical_path(&self) -> String {
+		let components: Vec<&str> = self.path.split('/')
+			.filter(|component| !component.is_empty() && *component != ".")
+			.collect();
+		let path = components.join("/");
+		match (self.path.starts_with('/'), self.path.ends_with('/') && !path.is_empty()) {
+			(true, true) => format!("/{}/", path),
+			(true, false) => format!("/{}", path),
+			(false, true) => format!("{}/", path),
+			(false, false) if path.is_empty() => ".".into(),
+			(false, false) => path
+		}
+	}
+	
+	/// The record's size as `usize` or an error if the size exceeds the addressable memory of the
+	/// target
+	pub fn payload_len_checked(&self) -> Result<usize, BasicTarError> {
+		usize::try_from(self.size)
+			.map_err(|_| BasicTarError::Unsupported("Payload size exceeds addressable memory"))
+	}
+	
+	/// The amount of bytes the record occupies in an archive (the header block, the payload and
+	/// the padding)
+	pub fn record_len(&self) -> Result<u64, BasicTarError> {
+		self.record_len_with(raw::BLOCK_LEN as u64)
+	}
+	/// The amount of bytes the record occupies in an archive whose payloads are padded to a
+	/// multiple of `block_len` instead of the standard block size
+	///
+	/// _Note: the header itself always occupies one standard block (512 bytes); a `block_len` of
+	/// zero is treated as one_
+	pub fn record_len_with(&self, block_len: u64) -> Result<u64, BasicTarError> {
+		let block_len = max(block_len, 1);
+		let padding = match self.size % block_len {
+			0 => 0,
+			rem => block_len - rem
+		};
+		(raw::BLOCK_LEN as u64).checked_add(self.size)
+			.and_then(|len| len.checked_add(padding))
+			.ok_or(BasicTarError::Unsupported("Record length exceeds the 64-bit range"))
+	}
+	
+	/// The authoritative payload length given the pax key/value `pairs` that apply to this record
+	///
+	/// _Note: the last pax `size` key takes precedence over the header's size field_
+	pub fn effective_size(&self, pairs: &[(String, String)]) -> Result<u64, BasicTarError> {
+		match pairs.iter().rev().find(|(key, _)| key == "size") {
+			Some((_, size)) => match size.parse() {
+				Ok(size) if size > raw::MAX_SIZE =>
+					Err(BasicTarError::InvalidData("Size is too large")),
+				Ok(size) => Ok(size),
+				Err(_) => Err(BasicTarError::InvalidData("Invalid pax size"))
+			},
+			None => Ok(self.size)
+		}
+	}
+	
+	/// Whether the record is a regular file (this includes the legacy NUL type flag)
+	pub fn is_regular_file(&self) -> bool {
+		match self.typeflag {
+			TypeFlag::REGULAR => true,
+			TypeFlag::REGULAR_LEGACY => !self.path.ends_with('/'),
+			_ => false
+		}
+	}
+	/// Whether the record is a directory (this includes legacy NUL type flag records with a
+	/// trailing `/`)
+	pub fn is_directory(&self) -> bool {
+		match self.typeflag {
+			TypeFlag::DIRECTORY => true,
+			TypeFlag::REGULAR_LEGACY => self.path.ends_with('/'),
+			_ => false
+		}
+	}
+	/// The record's link target if the record is a hardlink or a symlink
+	///
+	/// _Note: unlike the raw `linkname` field, this returns `None` for all other record types even
+	/// if a (stale) link name is set_
+	pub fn link_target(&self) -> Option<&str> {
+		match self.typeflag {
+			TypeFlag::HARDLINK | TypeFlag::SYMLINK => self.linkname.as_deref(),
+			_ => None
+		}
+	}
+	/// Replaces the legacy NUL type flag with `TypeFlag::REGULAR` or `TypeFlag::DIRECTORY` (if the
+	/// path has a trailing `/`) so that the record can be handled like a POSIX record
+	pub fn normalize_typeflag(&mut self) {
+		if self.typeflag == TypeFlag::REGULAR_LEGACY {
+			self.typeflag = match self.path.ends_with('/') {
+				true => TypeFlag::DIRECTORY,
+				false => TypeFlag::REGULAR
+			};
+		}
+	}
+	
+	/// Whether the record is a symlink
+	pub fn is_symlink(&self) -> bool {
+		self.typeflag == TypeFlag::SYMLINK
+	}
+	/// Whether the record is a hardlink
+	pub fn is_hardlink(&self) -> bool {
+		self.typeflag == TypeFlag::HARDLINK
+	}
+	/// Whether the record is a character or block device
+	pub fn is_device(&self) -> bool {
+		self.typeflag == TypeFlag::CHAR_DEV || self.typeflag == TypeFlag::BLOCK_DEV
+	}
+	/// Whether the record is a GNU volume header (which names the volume and can usually be
+	/// skipped)
+	pub fn is_volume_header(&self) -> bool {
+		self.typeflag == TypeFlag::GNU_VOLHDR
+	}
+	
+	/// The record's access mode bits as typed `Mode`
+	pub fn mode_bits(&self) -> Option<Mode> {
+		self.mode.map(Mode)
+	}
+	/// The record's access mode bits or a type-appropriate default if the mode is not set (0o755
+	/// for directories and 0o644 for everything else)
+	pub fn effective_mode(&self) -> u64 {
+		match (self.mode, self.is_directory()) {
+			(Some(mode), _) => mode,
+			(None, true) => 0o755,
+			(None, false) => 0o644
+		}
+	}
+	
+	/// Sets the record's path after validating that it can be serialized (i.e. that it is not
+	/// empty and fits into the name field or can be split into a USTAR prefix and a name)
+	///
+	/// _Note: the path is split when the header is serialized; on error, the path is not changed_
+	pub fn set_path(&mut self, path: impl Into<String>) -> Result<(), BasicTarError> {
+		let path = path.into();
+		self.check_path(&path)?;
+		self.path = path;
+		Ok(())
+	}
+	
+	/// The record's modification time as `SystemTime`
+	#[cfg(feature = "std")]
+	pub fn mtime_as_system_time(&self) -> Option<SystemTime> {
+		self.mtime.and_then(|mtime| UNIX_EPOCH.checked_add(Duration::from_secs(mtime)))
+	}
+	/// Sets the record's modification time from `time` (truncated to seconds)
+	#[cfg(feature = "std")]
+	pub fn set_mtime_from_system_time(&mut self, time: SystemTime) -> Result<(), BasicTarError> {
+		let mtime = time.duration_since(UNIX_EPOCH)
+			.map_err(|_| BasicTarError::InvalidData("Modification time is before the UNIX epoch"))?;
+		self.mtime = Some(mtime.as_secs());
+		Ok(())
+	}
+	
+	/// Validates that the header can be serialized and that it contains a known record type
+	pub fn validate(&self) -> Result<(), BasicTarError> {
+		let mut tar = raw::header::header();
+		
+		// Validate the path and link name
+		self.check_path(&self.path)?;
+		if self.linkname.as_ref().map(|name| name.len()).unwrap_or_default() > raw::LINKNAME_LEN {
+			Err(BasicTarError::Unsupported("Link name is too long"))?
+		}
+		
+		// Validate the user and group names
+		if self.uname.as_ref().map(|name| name.len()).unwrap_or_default() > raw::UNAME_LEN {
+			Err(BasicTarError::Unsupported("User name is too long"))?
+		}
+		if self.gname.as_ref().map(|name| name.len()).unwrap_or_default() > raw::GNAME_LEN {
+			Err(BasicTarError::Unsupported("Group name is too long"))?
+		}
+		
+		// Validate the numeric fields
+		self.mode.into_octal_field(&mut tar.mode)
+			.map_err(|_| BasicTarError::Unsupported("Mode is too large"))?;
+		self.uid.into_numeric_field(&mut tar.uid)
+			.map_err(|_| BasicTarError::Unsupported("UID is too large"))?;
+		self.gid.into_numeric_field(&mut tar.gid)
+			.map_err(|_| BasicTarError::Unsupported("GID is too large"))?;
+		self.size.into_numeric_field(&mut tar.size)
+			.map_err(|_| BasicTarError::Unsupported("Size is too large"))?;
+		self.mtime.into_numeric_field(&mut tar.mtime)
+			.map_err(|_| BasicTarError::Unsupported("Modification time is too large"))?;
+		
+		// Validate the type flag
+		match self.record_type() {
+			RecordType::Other(TypeFlag::REGULAR_LEGACY) | RecordType::Other(TypeFlag::RESERVED) =>
+				Ok(()),
+			RecordType::Other(TypeFlag::GNU_LONGNAME) | RecordType::Other(TypeFlag::GNU_LONGLINK) =>
+				Ok(()),
+			RecordType::Other(TypeFlag::GNU_SPARSE) | RecordType::Other(TypeFlag::GNU_VOLHDR) =>
+				Ok(()),
+			RecordType::Other(TypeFlag::GNU_MULTIVOL) | RecordType::Other(TypeFlag::GNU_DUMPDIR) =>
+				Ok(()),
+			RecordType::Other(_) => Err(BasicTarError::Unsupported("Unknown type flag")),
+			_ => Ok(())
+		}
 	}
 	
 	/// Serializes the tar header into a raw byte block
 	///
 	/// _Note: this function can fail because the struct may contain values that cannot be
-	/// serialized, e.g. a name longer than 100 bytes or a size greater than 8 GiB_
-	pub fn serialize(self) -> Result<raw::header::Raw, BasicTarError> {
-		// Create and populate the header
+	/// serialized, e.g. a path that cannot be split into a 155 byte prefix and a 100 byte name or
+	/// an UID that is too large to be encoded even as base-256 number_
+	pub fn serialize(&self) -> Result<raw::header::Raw, BasicTarError> {
+		let mut raw = raw::header::raw();
+		self.serialize_into(&mut raw)?;
+		Ok(raw)
+	}
+	/// Serializes the header directly into `out` (e.g. to reuse one block in a tight write loop)
+	///
+	/// _Note: `out` is left untouched if the serialization fails_
+	pub fn serialize_into(&self, out: &mut raw::header::Raw) -> Result<(), BasicTarError> {
+		let mut tar = self.populate()?;
+		Checksum::write(&mut tar);
+		*out = tar.into();
+		Ok(())
+	}
+	/// Serializes the header and writes it to `stream`
+	///
+	/// _Note: this function fails under the same conditions as `serialize` or if the write fails_
+	#[cfg(feature = "std")]
+	pub fn write_to(&self, mut stream: impl Write) -> Result<(), BasicTarError> {
+		let header_raw = self.serialize()?;
+		stream.try_write_exact(&header_raw, |_| {})?;
+		Ok(())
+	}
+	/// Serializes the tar header into a raw byte block but leaves the checksum field blank (eight
+	/// spaces) so that the checksum can be written later with `raw::rewrite_checksum`
+	///
+	/// _Note: this function fails under the same conditions as `serialize`_
+	pub fn serialize_unchecksummed(&self) -> Result<raw::header::Raw, BasicTarError> {
+		let mut tar = self.populate()?;
+		tar.checksum = [b' '; 8];
+		Ok(tar.into())
+	}
+	/// Creates a raw header and populates all fields except the checksum
+	fn populate(&self) -> Result<raw::header::Header, BasicTarError> {
 		let mut tar = raw::header::header();
-		self.path.into_field(&mut tar.name)?;
+		let (prefix, name) = self.split_path(&self.path)?;
+		name.write_field(&mut tar.name)?;
+		match self.format() {
+			Format::Gnu => {
+				// GNU headers store the access and change time in place of the USTAR prefix
+				if let Some(atime) = self.atime {
+					atime.into_octal_field(&mut tar.prefix[..12])?;
+				}
+				if let Some(ctime) = self.ctime {
+					ctime.into_octal_field(&mut tar.prefix[12..24])?;
+				}
+			},
+			_ => prefix.unwrap_or_default().write_field(&mut tar.prefix)?
+		}
 		
 		self.mode.into_octal_field(&mut tar.mode)?;
-		self.uid.into_octal_field(&mut tar.uid)?;
-		self.gid.into_octal_field(&mut tar.gid)?;
+		self.uid.into_numeric_field(&mut tar.uid)?;
+		self.gid.into_numeric_field(&mut tar.gid)?;
 		
-		self.size.into_octal_field(&mut tar.size)?;
-		self.mtime.into_octal_field(&mut tar.mtime)?;
+		self.size.into_numeric_field(&mut tar.size)?;
+		self.mtime.into_numeric_field(&mut tar.mtime)?;
 		
 		tar.typeflag[0] = self.typeflag;
-		self.linkname.into_field(&mut tar.linkname)?;
+		self.linkname.as_deref().unwrap_or_default().write_field(&mut tar.linkname)?;
 		
-		// Write the checksum and return the header
-		Checksum::write(&mut tar);
-		Ok(tar.into())
+		self.uname.as_deref().unwrap_or_default().write_field(&mut tar.uname)?;
+		self.gname.as_deref().unwrap_or_default().write_field(&mut tar.gname)?;
+		
+		// Classic headers are upgraded to USTAR if the path needs a prefix
+		let (magic, version) = match (prefix, self.magic, self.version) {
+			(Some(_), None, None) => (*b"ustar\0", *b"00"),
+			(_, magic, version) => (magic.unwrap_or_default(), version.unwrap_or_default())
+		};
+		tar.magic = magic;
+		tar.version = version;
+		if let Some(devmajor) = self.devmajor {
+			devmajor.into_octal_field(&mut tar.devmajor)?;
+		}
+		if let Some(devminor) = self.devminor {
+			devminor.into_octal_field(&mut tar.devminor)?;
+		}
+		Ok(tar)
 	}
-}
\ No newline at end of file
+	
+	/// Whether `tar` has a USTAR prefix field (GNU headers store the access and change time there)
+	fn has_prefix(tar: &raw::header::Header) -> bool {
+		(&tar.magic, &tar.version) == (b"ustar\0", b"00")
+	}
+	/// Decodes the access and change time of a GNU header (both are `None` if `tar` is not a GNU
+	/// header)
+	fn decode_gnu_times(tar: &raw::header::Header)
+		-> Result<(Option<u64>, Option<u64>), BasicTarError>
+	{
+		match (&tar.magic, &tar.version) {
+			(b"ustar ", b" \0") => {
+				let atime = Option::from_octal_field(&tar.prefix[..12])?;
+				let ctime = Option::from_octal_field(&tar.prefix[12..24])?;
+				Ok((atime, ctime))
+			},
+			_ => Ok((None, None))
+		}
+	}
+	
+	/// Checks that `path` is not empty and can be stored in this header (see `split_path`)
+	fn check_path(&self, path: &str) -> Result<(), BasicTarError> {
+		if path.is_empty() {
+			Err(BasicTarError::InvalidData("Path is empty"))?
+		}
+		self.split_path(path)?;
+		Ok(())
+	}
+	/// Splits `path` into an optional USTAR prefix and a name that fits into the name field
+	///
+	/// _Note: GNU headers have no prefix, so the path must fit into the name field (longer paths
+	/// need a GNU long name record, see `ArchiveWriter::gnu_long_names`); classic headers without
+	/// magic are upgraded to USTAR if they need a prefix, classic headers with a foreign magic
+	/// never have a prefix_
+	fn split_path<'a>(&self, path: &'a str) -> Result<(Option<&'a str>, &'a str), BasicTarError> {
+		match self.format() {
+			Format::Ustar | Format::Pax =>
+				Self::split_ustar_path(path, raw::NAME_LEN, raw::PREFIX_LEN),
+			Format::Classic if self.magic.is_none() && self.version.is_none() =>
+				Self::split_ustar_path(path, raw::NAME_LEN, raw::PREFIX_LEN),
+			_ if path.len() <= raw::NAME_LEN => Ok((None, path)),
+			Format::Gnu => Err(BasicTarError::Unsupported("Path too long for GNU name field")),
+			_ => Err(BasicTarError::Unsupported("Path too long for name field"))
+		}
+	}
+	/// Splits `path` into an optional USTAR prefix and a name so that the name fits into
+	/// `name_len` bytes and the prefix into `prefix_len` bytes
+	fn split_ustar_path(path: &str, name_len: usize, prefix_len: usize)
+		-> Result<(Option<&str>, &str), BasicTarError>
+	{
+		// Check if the path fits into the name field
+		if path.len() <= name_len {
+			return Ok((None, path))
+		}
+		
+		// Find the last `/` that results in a valid split
+		let split = path.char_indices().rev()
+			.filter(|(idx, char)| *char == '/' && *idx <= prefix_len)
+			.map(|(idx, _)| (&path[..idx], &path[idx + 1..]))
+			.find(|(prefix, name)| {
+				!prefix.is_empty() && !name.is_empty() && name.len() <= name_len
+			});
+		match split {
+			Some((prefix, name)) => Ok((Some(prefix), name)),
+			None => Err(BasicTarError::Unsupported("Path too long for ustar prefix/name split"))
+		}
+	}
+}
+
+
+/// A builder to construct a `Header`
+#[derive(Debug, Clone)]
+pub struct HeaderBuilder {
+	header: Header
+}
+impl HeaderBuilder {
+	/// Creates a new builder for a regular file header with path `path` and size 0
+	pub fn new(path: impl Into<String>) -> Self {
+		let header = Header{ path: path.into(), typeflag: TypeFlag::REGULAR, ..Default::default() };
+		Self{ header }
+	}
+	
+	/// Sets the record's access mode bits (e.g. 0o777)
+	pub fn mode(mut self, mode: u64) -> Self {
+		self.header.mode = Some(mode);
+		self
+	}
+	/// Sets the record's UID
+	pub fn uid(mut self, uid: u64) -> Self {
+		self.header.uid = Some(uid);
+		self
+	}
+	/// Sets the record's GID
+	pub fn gid(mut self, gid: u64) -> Self {
+		self.header.gid = Some(gid);
+		self
+	}
+	/// Sets the record's size
+	pub fn size(mut self, size: u64) -> Self {
+		self.header.size = size;
+		self
+	}
+	/// Sets the record's modification time
+	pub fn mtime(mut self, mtime: u64) -> Self {
+		self.header.mtime = Some(mtime);
+		self
+	}
+	/// Sets the record's device major and minor number (for character and block devices)
+	pub fn device(mut self, devmajor: u64, devminor: u64) -> Self {
+		self.header.devmajor = Some(devmajor);
+		self.header.devminor = Some(devminor);
+		self
+	}
+	/// Sets the record's type
+	pub fn typeflag(mut self, typeflag: u8) -> Self {
+		self.header.typeflag = typeflag;
+		self
+	}
+	/// Sets the record's link name
+	pub fn linkname(mut self, linkname: String) -> Self {
+		self.header.linkname = Some(linkname);
+		self
+	}
+	
+	/// Builds the header
+	pub fn build(self) -> Header {
+		self.header
+	}
+}
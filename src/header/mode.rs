@@ -0,0 +1,53 @@
+use core::fmt::{ self, Display, Formatter };
+
+
+/// A typed wrapper around the record's access mode bits
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Mode(pub u64);
+impl Mode {
+	/// The set-user-ID bit
+	pub const SETUID: u64 = 0o4000;
+	/// The set-group-ID bit
+	pub const SETGID: u64 = 0o2000;
+	/// The sticky bit
+	pub const STICKY: u64 = 0o1000;
+	/// The mask for the user, group and other permission bits
+	pub const PERMISSIONS: u64 = 0o777;
+	
+	/// Whether the set-user-ID bit is set
+	pub fn is_setuid(&self) -> bool {
+		self.0 & Self::SETUID != 0
+	}
+	/// Whether the set-group-ID bit is set
+	pub fn is_setgid(&self) -> bool {
+		self.0 & Self::SETGID != 0
+	}
+	/// Whether the sticky bit is set
+	pub fn is_sticky(&self) -> bool {
+		self.0 & Self::STICKY != 0
+	}
+	/// The user, group and other permission bits (the low 9 bits)
+	pub fn permissions(&self) -> u64 {
+		self.0 & Self::PERMISSIONS
+	}
+}
+impl From<u64> for Mode {
+	fn from(mode: u64) -> Self {
+		Self(mode)
+	}
+}
+impl From<Mode> for u64 {
+	fn from(mode: Mode) -> Self {
+		mode.0
+	}
+}
+impl Display for Mode {
+	/// Formats the permission bits like `ls` does (e.g. `rwxr-xr-x`) without the special bits
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let permissions = self.permissions();
+		(0..9).rev().try_for_each(|bit| match permissions & (1 << bit) {
+			0 => write!(f, "-"),
+			_ => write!(f, "{}", ['x', 'w', 'r'][bit % 3])
+		})
+	}
+}
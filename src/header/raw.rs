@@ -1,16 +1,53 @@
 //! The raw representation of the TAR header fields and some byte constants
 
 use crate::BasicTarError;
-use std::{ iter, mem };
+use alloc::{ format, string::String };
+use core::{ iter, ops::Add };
 
 
 /// The length of a tar block
 pub const BLOCK_LEN: usize = 512;
 
+/// The maximum length of the name field
+pub const NAME_LEN: usize = 100;
+/// The maximum length of the link name field
+pub const LINKNAME_LEN: usize = 100;
+/// The maximum length of the user name field
+pub const UNAME_LEN: usize = 32;
+/// The maximum length of the group name field
+pub const GNAME_LEN: usize = 32;
+/// The maximum length of the USTAR prefix field
+pub const PREFIX_LEN: usize = 155;
+/// The maximum size that can be represented as octal number in the size field (8 GiB - 1); larger
+/// sizes are encoded as GNU base-256 number
+pub const MAX_OCTAL_SIZE: u64 = 0o77777777777;
+/// The maximum supported record size; like GNU tar's `off_t`, sizes are limited to the positive
+/// range of an `i64` so that the record length computations can never overflow
+pub const MAX_SIZE: u64 = i64::MAX as u64;
+
+/// An all-zero block; an archive is terminated by two consecutive zero blocks (the end of archive
+/// indicator)
+pub const EOF_BLOCK: header::Raw = header::raw();
+/// Checks whether `raw` is an all-zero block (which is usually part of an end of archive
+/// indicator)
+pub fn is_zero_block(raw: &header::Raw) -> bool {
+	raw.iter().all(|b| *b == 0)
+}
+/// Checks whether `raw` is an all-zero or an all-space block; both are treated as empty header
+///
+/// _Note: some legacy tar writers terminate the archive with all-space instead of all-zero blocks_
+pub fn is_empty_block(raw: &header::Raw) -> bool {
+	is_zero_block(raw) || raw.iter().all(|b| *b == b' ')
+}
+
 
 /// Defines the classic old-style tar header
 pub mod header {
-	use super::{ mem, BLOCK_LEN };
+	use super::{
+		BLOCK_LEN, NAME_LEN, LINKNAME_LEN, UNAME_LEN, GNAME_LEN, PREFIX_LEN, MAX_SIZE, U64Ext
+	};
+	use crate::BasicTarError;
+	use core::{ convert::TryFrom, mem };
 	
 	/// A raw header block
 	pub type Raw = [u8; BLOCK_LEN];
@@ -19,11 +56,11 @@ pub mod header {
 		[0; BLOCK_LEN]
 	}
 	
-	/// The 1:1-byte representation of the classic old-style tar header
-	#[repr(packed)]
+	/// The 1:1-byte representation of the classic old-style tar header including the USTAR
+	/// extension fields
 	#[derive(Copy, Clone)]
 	pub struct Header {
-		pub name: [u8; 100],
+		pub name: [u8; NAME_LEN],
 		pub mode: [u8; 8],
 		pub uid: [u8; 8],
 		pub gid: [u8; 8],
@@ -31,25 +68,326 @@ pub mod header {
 		pub mtime: [u8; 12],
 		pub checksum: [u8; 8],
 		pub typeflag: [u8; 1],
-		pub linkname: [u8; 100],
-		pub _extra: [u8; 243],
+		pub linkname: [u8; LINKNAME_LEN],
+		pub magic: [u8; 6],
+		pub version: [u8; 2],
+		pub uname: [u8; UNAME_LEN],
+		pub gname: [u8; GNAME_LEN],
+		pub devmajor: [u8; 8],
+		pub devminor: [u8; 8],
+		pub prefix: [u8; PREFIX_LEN],
 		pub _pad: [u8; 12]
 	}
+	impl Header {
+		/// The raw type flag byte
+		pub fn typeflag_byte(&self) -> u8 {
+			self.typeflag[0]
+		}
+		/// The decoded size field without parsing the entire header
+		pub fn size_octal(&self) -> Result<u64, BasicTarError> {
+			match u64::from_octal_field(&self.size)? {
+				size if size > MAX_SIZE => Err(BasicTarError::InvalidData("Size is too large")),
+				size => Ok(size)
+			}
+		}
+		/// The decoded modification time as signed number of seconds since the UNIX epoch
+		///
+		/// _Note: unlike `Header::parse`, this also decodes negative GNU base-256 numbers (two's
+		/// complement with a leading `0xff` byte) that represent times before 1970_
+		pub fn mtime_signed(&self) -> Result<i64, BasicTarError> {
+			if self.mtime[0] != 0xff {
+				let mtime = u64::from_octal_field(&self.mtime)?;
+				return i64::try_from(mtime)
+					.map_err(|_| BasicTarError::Unsupported("Base-256 number is too large"))
+			}
+			
+			// Decode the two's complement and check that it fits into an `i64`
+			let (leading, bytes) = self.mtime.split_at(self.mtime.len() - mem::size_of::<i64>());
+			let mtime = bytes.iter().fold(0, |num, byte| (num << 8) | *byte as i64);
+			match leading.iter().all(|b| *b == 0xff) && mtime < 0 {
+				true => Ok(mtime),
+				false => Err(BasicTarError::Unsupported("Base-256 number is too large"))
+			}
+		}
+		/// The name field up to (excluding) the first NUL byte
+		pub fn name_bytes(&self) -> &[u8] {
+			trim_nul(&self.name)
+		}
+		/// The used length of the name field (which is `NAME_LEN` if the name is not
+		/// NUL-terminated)
+		///
+		/// _Note: a name that uses the entire field may be continued by the USTAR prefix_
+		pub fn name_len(&self) -> usize {
+			self.name_bytes().len()
+		}
+		/// The link name field up to (excluding) the first NUL byte
+		pub fn linkname_bytes(&self) -> &[u8] {
+			trim_nul(&self.linkname)
+		}
+		/// The USTAR prefix field up to (excluding) the first NUL byte
+		pub fn prefix_bytes(&self) -> &[u8] {
+			trim_nul(&self.prefix)
+		}
+		
+		/// A copy of the bytes 257..500 (the USTAR magic, version, user and group names, device
+		/// numbers and prefix) to inspect vendor-specific fields that are not modeled by this crate
+		///
+		/// _Note: the bytes are returned by value since the fields are not stored contiguously_
+		pub fn extra_bytes(&self) -> [u8; 243] {
+			read(&Raw::from(*self), 257)
+		}
+		
+		/// Compares all fields except the checksum (e.g. to check whether a header was
+		/// re-serialized identically)
+		pub fn eq_ignoring_checksum(&self, other: &Self) -> bool {
+			let (mut this, mut other) = (*self, *other);
+			this.checksum = [0; 8];
+			other.checksum = [0; 8];
+			Raw::from(this)[..] == Raw::from(other)[..]
+		}
+	}
 	/// Creates a new all-zero header
 	pub fn header() -> Header {
 		Header::from(raw())
 	}
 	impl From<Raw> for Header {
 		fn from(raw: Raw) -> Self {
-			assert_eq!(mem::size_of_val(&raw), mem::size_of::<Self>());
-			unsafe{ mem::transmute(raw) }
+			Self::from(&raw)
+		}
+	}
+	impl From<&Raw> for Header {
+		fn from(raw: &Raw) -> Self {
+			Self {
+				name: read(raw, 0), mode: read(raw, 100), uid: read(raw, 108),
+				gid: read(raw, 116), size: read(raw, 124), mtime: read(raw, 136),
+				checksum: read(raw, 148), typeflag: read(raw, 156), linkname: read(raw, 157),
+				magic: read(raw, 257), version: read(raw, 263), uname: read(raw, 265),
+				gname: read(raw, 297), devmajor: read(raw, 329), devminor: read(raw, 337),
+				prefix: read(raw, 345), _pad: read(raw, 500)
+			}
+		}
+	}
+	impl From<Header> for Raw {
+		fn from(header: Header) -> Self {
+			let mut raw = raw();
+			write(&mut raw, 0, &header.name);
+			write(&mut raw, 100, &header.mode);
+			write(&mut raw, 108, &header.uid);
+			write(&mut raw, 116, &header.gid);
+			write(&mut raw, 124, &header.size);
+			write(&mut raw, 136, &header.mtime);
+			write(&mut raw, 148, &header.checksum);
+			write(&mut raw, 156, &header.typeflag);
+			write(&mut raw, 157, &header.linkname);
+			write(&mut raw, 257, &header.magic);
+			write(&mut raw, 263, &header.version);
+			write(&mut raw, 265, &header.uname);
+			write(&mut raw, 297, &header.gname);
+			write(&mut raw, 329, &header.devmajor);
+			write(&mut raw, 337, &header.devminor);
+			write(&mut raw, 345, &header.prefix);
+			write(&mut raw, 500, &header._pad);
+			raw
+		}
+	}
+	
+	/// Returns `field` up to (excluding) the first NUL byte
+	fn trim_nul(field: &[u8]) -> &[u8] {
+		let nul = field.iter().position(|b| *b == 0x00).unwrap_or(field.len());
+		&field[..nul]
+	}
+	/// Copies the `N` bytes at `offset` out of `raw`
+	fn read<const N: usize>(raw: &Raw, offset: usize) -> [u8; N] {
+		let mut field = [0; N];
+		field.copy_from_slice(&raw[offset..offset + N]);
+		field
+	}
+	/// Copies `field` into `raw` at `offset`
+	fn write(raw: &mut Raw, offset: usize, field: &[u8]) {
+		raw[offset..offset + field.len()].copy_from_slice(field)
+	}
+}
+
+
+/// Defines the pax extended header records
+pub mod pax {
+	use crate::BasicTarError;
+	use alloc::{
+		format,
+		string::{ String, ToString },
+		vec::Vec
+	};
+	
+	/// Parses the "length key=value\n"-records in a pax extended header `payload` into key-value
+	/// pairs
+	///
+	/// _Note: the records are delimited by their length prefix and split at the first `=` only, so
+	/// values may contain `=` and newlines (e.g. for xattr or ACL keys)_
+	///
+	/// _Note: keys and values must be UTF-8; use `parse_bytes` for payloads with binary values
+	/// (e.g. xattrs)_
+	pub fn parse(payload: &[u8]) -> Result<Vec<(String, String)>, BasicTarError> {
+		parse_bytes(payload)?.into_iter()
+			.map(|(key, value)| match String::from_utf8(value) {
+				Ok(value) => Ok((key, value)),
+				Err(_) => Err(BasicTarError::InvalidData("Pax record is not UTF-8"))
+			})
+			.collect()
+	}
+	/// Parses the "length key=value\n"-records in a pax extended header `payload` into key-value
+	/// pairs with raw byte values
+	///
+	/// _Note: only the keys must be UTF-8_
+	pub fn parse_bytes(mut payload: &[u8]) -> Result<Vec<(String, Vec<u8>)>, BasicTarError> {
+		let mut pairs = Vec::new();
+		while !payload.is_empty() {
+			// Parse the length prefix
+			let space = payload.iter().position(|b| *b == b' ')
+				.ok_or(BasicTarError::InvalidData("Invalid pax record length"))?;
+			let len: usize = core::str::from_utf8(&payload[..space]).ok()
+				.filter(|len| !len.is_empty() && len.bytes().all(|b| b.is_ascii_digit()))
+				.and_then(|len| len.parse().ok())
+				.filter(|len| *len > space && *len <= payload.len())
+				.ok_or(BasicTarError::InvalidData("Invalid pax record length"))?;
+			
+			// Get the record and validate the trailing newline
+			let (record, remaining) = payload.split_at(len);
+			let record = match record.split_last() {
+				Some((b'\n', record)) => &record[space + 1..],
+				_ => Err(BasicTarError::InvalidData("Pax record is not newline-terminated"))?
+			};
+			
+			// Split the record into key and value
+			let (key, value) = match record.iter().position(|b| *b == b'=') {
+				Some(equals) if equals > 0 => (&record[..equals], &record[equals + 1..]),
+				_ => Err(BasicTarError::InvalidData("Pax record has no key"))?
+			};
+			let key = core::str::from_utf8(key)
+				.map_err(|_| BasicTarError::InvalidData("Pax record is not UTF-8"))?;
+			pairs.push((key.into(), value.to_vec()));
+			payload = remaining;
 		}
+		Ok(pairs)
+	}
+	
+	/// Extracts the extended attributes from the `SCHILY.xattr.`-prefixed key-value `pairs` (like
+	/// GNU tar and bsdtar write them) as attribute name and value pairs
+	///
+	/// _Note: following the schily convention, the values are stored as raw bytes (and not base64
+	/// encoded), so `pairs` should be parsed with `parse_bytes`_
+	pub fn xattrs(pairs: &[(String, Vec<u8>)]) -> Vec<(String, Vec<u8>)> {
+		pairs.iter()
+			.filter_map(|(key, value)| {
+				let name = key.strip_prefix("SCHILY.xattr.")?;
+				Some((name.to_string(), value.clone()))
+			})
+			.collect()
 	}
-	impl Into<Raw> for Header {
-		fn into(self) -> Raw {
-			assert_eq!(mem::size_of_val(&self), mem::size_of::<Raw>());
-			unsafe{ mem::transmute(self) }
+	
+	/// Serializes the key-value `pairs` into "length key=value\n"-records for a pax extended
+	/// header payload
+	pub fn serialize(pairs: &[(&str, &str)]) -> Vec<u8> {
+		let mut payload = Vec::new();
+		for (key, value) in pairs {
+			// Compute the length which includes its own digits until it is stable
+			let unprefixed = key.len() + value.len() + 3;
+			let mut len = unprefixed;
+			while len != unprefixed + len.to_string().len() {
+				len = unprefixed + len.to_string().len();
+			}
+			
+			// Write the record
+			payload.extend_from_slice(format!("{} {}={}\n", len, key, value).as_bytes());
 		}
+		payload
+	}
+}
+
+/// Defines the GNU sparse file header fields
+pub mod gnu_sparse {
+	use super::{ header::Raw, U64Ext };
+	use crate::BasicTarError;
+	use alloc::vec::Vec;
+	
+	/// The offset of the in-header sparse map
+	const SPARSE_OFFSET: usize = 386;
+	/// The amount of entries in the in-header sparse map
+	const SPARSE_ENTRIES: usize = 4;
+	/// The offset of the `isextended`-flag
+	const IS_EXTENDED_OFFSET: usize = 482;
+	/// The offset of the real size field
+	const REAL_SIZE_OFFSET: usize = 483;
+	
+	/// Parses the in-header sparse map of the raw GNU sparse header `raw` into a list of
+	/// `(offset, length)`-pairs
+	///
+	/// _Note: continuation blocks (indicated by the `isextended`-flag) are not supported yet_
+	pub fn parse(raw: &Raw) -> Result<Vec<(u64, u64)>, BasicTarError> {
+		if raw[IS_EXTENDED_OFFSET] != 0 {
+			Err(BasicTarError::Unsupported("GNU sparse continuation blocks are not supported"))?
+		}
+		
+		// Parse the entries until the first empty entry
+		let mut map = Vec::new();
+		for entry in raw[SPARSE_OFFSET..].chunks(24).take(SPARSE_ENTRIES) {
+			match Option::from_octal_field(&entry[..12])? {
+				Some(offset) => map.push((offset, u64::from_octal_field(&entry[12..])?)),
+				None => break
+			}
+		}
+		Ok(map)
+	}
+	
+	/// Parses the real (expanded) size of the sparse file from the raw GNU sparse header `raw`
+	pub fn real_size(raw: &Raw) -> Result<u64, BasicTarError> {
+		u64::from_octal_field(&raw[REAL_SIZE_OFFSET..REAL_SIZE_OFFSET + 12])
+	}
+}
+
+
+/// Defines the GNU dumpdir payload of incremental backups
+pub mod gnu_dumpdir {
+	use crate::BasicTarError;
+	use alloc::{ string::String, vec::Vec };
+	
+	/// Parses the dumpdir `payload` into a list of `(status, name)`-pairs where `status` is the
+	/// GNU status byte (e.g. `b'Y'` for an archived entry or `b'D'` for a directory)
+	pub fn parse(mut payload: &[u8]) -> Result<Vec<(u8, String)>, BasicTarError> {
+		let mut entries = Vec::new();
+		while !payload.is_empty() {
+			// Get the NUL-terminated entry
+			let nul = payload.iter().position(|b| *b == 0x00)
+				.ok_or(BasicTarError::InvalidData("Dumpdir entry is not NUL-terminated"))?;
+			let (entry, remaining) = (&payload[..nul], &payload[nul + 1..]);
+			
+			// Split the entry into status and name or stop at the terminating empty entry
+			match entry.split_first() {
+				Some((status, name)) => {
+					let name = String::from_utf8(name.to_vec())
+						.map_err(|_| BasicTarError::Unsupported("Dumpdir entry is not UTF-8"))?;
+					entries.push((*status, name))
+				},
+				None => break
+			}
+			payload = remaining;
+		}
+		Ok(entries)
+	}
+}
+
+
+/// Defines the GNU multi-volume continuation header fields
+pub mod gnu_multivol {
+	use super::{ header::Raw, U64Ext };
+	use crate::BasicTarError;
+	
+	/// The offset of the continuation offset field
+	const OFFSET_OFFSET: usize = 369;
+	
+	/// Parses the offset of the continued payload within the original file from the raw GNU
+	/// multi-volume continuation header `raw`
+	pub fn offset(raw: &Raw) -> Result<u64, BasicTarError> {
+		u64::from_octal_field(&raw[OFFSET_OFFSET..OFFSET_OFFSET + 12])
 	}
 }
 
@@ -59,6 +397,11 @@ pub struct TypeFlag;
 impl TypeFlag {
 	/// The type flag for a regular file
 	pub const REGULAR: u8 = b'0';
+	/// The legacy type flag for a regular file used by historic (pre-POSIX) implementations
+	///
+	/// _Note: historic implementations also use this type flag for directories, which are marked by
+	/// a trailing `/` in the path_
+	pub const REGULAR_LEGACY: u8 = 0x00;
 	/// The type flag for a hardlink
 	pub const HARDLINK: u8 = b'1';
 	/// The type flag for a symlink
@@ -78,30 +421,119 @@ impl TypeFlag {
 	pub const PAX_SINGLE: u8 = b'x';
 	/// The type flag for a pax interchange record that affects all subsequent files
 	pub const PAX_GLOBAL: u8 = b'g';
+	/// The type flag for a GNU record whose payload is the path of the next record
+	pub const GNU_LONGNAME: u8 = b'L';
+	/// The type flag for a GNU record whose payload is the link name of the next record
+	pub const GNU_LONGLINK: u8 = b'K';
+	/// The type flag for a GNU sparse file
+	pub const GNU_SPARSE: u8 = b'S';
+	/// The type flag for a GNU dumpdir record whose payload lists the directory contents
+	pub const GNU_DUMPDIR: u8 = b'D';
+	/// The type flag for a GNU volume header whose path is the volume name
+	pub const GNU_VOLHDR: u8 = b'V';
+	/// The type flag for a GNU multi-volume continuation of a record from the previous volume
+	pub const GNU_MULTIVOL: u8 = b'M';
+	
+	/// All known type flags paired with a human-readable name (e.g. to list the supported record
+	/// types)
+	pub fn all() -> &'static [(u8, &'static str)] {
+		&[
+			(Self::REGULAR, "regular file"), (Self::REGULAR_LEGACY, "legacy regular file"),
+			(Self::HARDLINK, "hard link"), (Self::SYMLINK, "symbolic link"),
+			(Self::CHAR_DEV, "character device"), (Self::BLOCK_DEV, "block device"),
+			(Self::DIRECTORY, "directory"), (Self::FIFO_NODE, "fifo"),
+			(Self::PAX_SINGLE, "pax extended header"),
+			(Self::PAX_GLOBAL, "pax global extended header"),
+			(Self::GNU_LONGNAME, "GNU long name"), (Self::GNU_LONGLINK, "GNU long link"),
+			(Self::GNU_SPARSE, "GNU sparse file"), (Self::GNU_DUMPDIR, "GNU dumpdir"),
+			(Self::GNU_VOLHDR, "GNU volume header"),
+			(Self::GNU_MULTIVOL, "GNU multi-volume continuation")
+		]
+	}
+}
+
+
+/// Computes the (unsigned) checksum over the raw header `raw`
+pub fn checksum(raw: &header::Raw) -> u64 {
+	sum_header_bytes(raw, |byte| byte as u64)
+}
+/// Sums up all bytes of the raw header `raw` converted with `convert`, with the checksum field
+/// treated as eight spaces
+fn sum_header_bytes<T: Add<Output = T> + Default>(raw: &header::Raw, convert: impl Fn(u8) -> T)
+	-> T
+{
+	raw[..148].iter().chain([b' '; 8].iter()).chain(raw[156..].iter())
+		.fold(T::default(), |sum, byte| sum + convert(*byte))
+}
+/// Decodes the checksum stored in the checksum field of the raw header `raw`
+///
+/// _Note: only the leading octal digits are decoded, so both the `"NNNNNN\0 "` and the
+/// `"NNNNNN \0"` layout (and any trailing garbage) are accepted_
+pub fn stored_checksum(raw: &header::Raw) -> Result<u64, BasicTarError> {
+	Checksum::parse_field(&raw[148..156])
+}
+/// Recomputes the checksum over the raw header `raw` and writes it into the checksum field
+///
+/// _Note: this is useful if you patch the raw header bytes directly (e.g. to rename a record in
+/// place) since the stored checksum goes stale otherwise_
+pub fn rewrite_checksum(raw: &mut header::Raw) {
+	let mut tar = header::Header::from(&*raw);
+	Checksum::write(&mut tar);
+	*raw = tar.into();
 }
 
 
 /// Helpers for checksum verification
 pub(in crate::header) struct Checksum;
 impl Checksum {
-	/// Computes the checksum over `raw` and writes it to the raw header
+	/// Computes the checksum over `raw` and writes it to the raw header in the canonical POSIX
+	/// form (six octal digits followed by a NUL and a space)
 	pub fn write(tar: &mut header::Header) {
-		Self::compute(*tar).into_octal_field(&mut tar.checksum)
-			.expect("We should always be able to octal-encode an `u32` into 8 bytes");
+		// The maximum checksum is `512 * 255 = 0o376000` so it always fits into six octal digits
+		let checksum = format!("{:06o}\0 ", Self::compute(*tar));
+		tar.checksum.copy_from_slice(checksum.as_bytes());
 	}
 	/// Verifies the checksum for `raw`
+	///
+	/// _Note: for compatibility with some historic implementations, the checksum is also accepted
+	/// if it matches the sum over the header bytes as signed `i8`_
+	///
+	/// _Note: the checksum must be an octal number with at most six significant digits_
 	pub fn verify(tar: &header::Header) -> Result<(), BasicTarError> {
-		match Self::compute(*tar) == u64::from_octal_field(&tar.checksum)? {
+		// Reject base-256 checksums and checksums with more than six significant octal digits
+		if tar.checksum[0] & 0x80 != 0 {
+			Err(BasicTarError::InvalidData("Header checksum is not octal"))?
+		}
+		let stored = Self::parse_field(&tar.checksum)?;
+		if stored > 0o777777 {
+			Err(BasicTarError::InvalidData("Header checksum is too large"))?
+		}
+		
+		match Self::compute(*tar) == stored || Self::compute_signed(*tar) == stored as i64 {
 			true => Ok(()),
 			false => Err(BasicTarError::InvalidData("Invalid header checksum"))
 		}
 	}
 	
+	/// Decodes the leading octal digits (after optional leading spaces) of the checksum field
+	/// `field` and ignores the terminator and anything after it
+	pub fn parse_field(field: &[u8]) -> Result<u64, BasicTarError> {
+		let start = field.iter().position(|b| *b != b' ').unwrap_or(field.len());
+		let digits = field[start..].iter().take_while(|b| (b'0'..=b'7').contains(*b));
+		match digits.clone().count() {
+			0 => Err(BasicTarError::InvalidData("Header checksum is not octal")),
+			_ => Ok(digits.fold(0, |num, digit| (num << 3) + (digit - b'0') as u64))
+		}
+	}
+	
 	/// Computes the checksum
 	fn compute(tar: header::Header) -> u64 {
-		let raw: header::Raw = tar.into();
-		raw[..148].iter().chain([b' '; 8].iter()).chain(raw[156..].iter())
-			.fold(0, |sum, byte| sum + (*byte as u64))
+		checksum(&tar.into())
+	}
+	/// Computes the checksum over the header bytes as signed `i8` like some historic
+	/// implementations did
+	fn compute_signed(tar: header::Header) -> i64 {
+		sum_header_bytes(&tar.into(), |byte| byte as i8 as i64)
 	}
 }
 
@@ -109,19 +541,48 @@ impl Checksum {
 /// An extension for `u64`
 pub(in crate::header) trait U64Ext: Sized {
 	/// Creates a new `u64` from an octal string
+	///
+	/// _Note: the terminator is optional, so fields that are completely filled with digits are
+	/// accepted too; the digits are never read past the end of `field` and anything that is not a
+	/// valid `u64` is rejected as `InvalidData`_
+	///
+	/// _Note: like GNU tar, a single leading `+` (e.g. `"+644\0"`) is accepted_
 	fn from_octal_field(field: &[u8]) -> Result<Self, BasicTarError>;
 	/// Creates an octal string from `self`
 	fn into_octal_field(self, field: &mut[u8]) -> Result<(), BasicTarError>;
+	/// Creates an octal string from `self`
+	///
+	/// _Note: if `self` is too large to be represented as octal string, it is encoded as GNU
+	/// base-256 number instead; like GNU tar, this should only be used for the size, uid, gid
+	/// and mtime fields_
+	fn into_numeric_field(self, field: &mut[u8]) -> Result<(), BasicTarError>;
+	
+	/// Creates a new `u64` from a GNU base-256 field (the high bit of the first byte is set, the
+	/// sign bit `0x40` is cleared and the remaining bits are a big-endian integer)
+	fn from_base256_field(field: &[u8]) -> Result<Self, BasicTarError>;
+	/// Writes `self` as GNU base-256 number to `field`
+	fn into_base256_field(self, field: &mut[u8]) -> Result<(), BasicTarError>;
 }
 impl U64Ext for Option<u64> {
 	fn from_octal_field(field: &[u8]) -> Result<Self, BasicTarError> {
-		let string = Option::<String>::from_terminated_field(field)?;
-		let octal = match string.as_ref().map(|s| s.trim_end()) {
-			Some(octal) if octal.len() > 0 => octal,
+		// Check for a base-256 encoded number
+		match field.first() {
+			Some(byte) if byte & 0xC0 == 0xC0 =>
+				Err(BasicTarError::Unsupported("Negative base-256 numbers are not supported"))?,
+			Some(byte) if byte & 0x80 != 0 => return Self::from_base256_field(field),
+			_ => ()
+		}
+		
+		// Skip leading spaces and decode the string
+		let start = field.iter().position(|b| *b != b' ').unwrap_or(field.len());
+		let string = Option::<String>::from_terminated_field(&field[start..])?;
+		let octal = match string.as_ref().map(|s| s.trim()) {
+			Some(octal) if !octal.is_empty() => octal,
 			_ => return Ok(None)
 		};
 		
-		let num = u64::from_str_radix(&octal, 8)
+		// `from_str_radix` accepts a single leading `+` and rejects a leading `-`
+		let num = u64::from_str_radix(octal, 8)
 			.map_err(|_| BasicTarError::InvalidData("Invalid octal number"))?;
 		Ok(Some(num))
 	}
@@ -130,13 +591,56 @@ impl U64Ext for Option<u64> {
 		let num = self.map(|num| format!("{:o}", num)).unwrap_or_default();
 		
 		// Compute the amount of "0"-literals to prepend
-		let available = field.len().checked_sub(1).unwrap_or(0);
-		let pad = available.checked_sub(num.len()).unwrap_or(0);
+		let available = field.len().saturating_sub(1);
+		if num.len() > available {
+			Err(BasicTarError::FieldTooSmall{ needed: num.len() + 1, available: field.len() })?
+		}
 		
 		// Create the padded string and write it to the field
-		let num: String = iter::repeat('0').take(pad).chain(num.chars()).collect();
+		let num = format!("{:0>width$}", num, width = available);
 		num.into_terminated_field(field)
 	}
+	fn into_numeric_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		// Fall back to base-256 if the octal number is too long
+		match self.into_octal_field(field) {
+			Err(BasicTarError::FieldTooSmall{ .. }) => self.into_base256_field(field),
+			result => result
+		}
+	}
+	
+	fn from_base256_field(field: &[u8]) -> Result<Self, BasicTarError> {
+		// Get the big-endian digits without the marker and sign bits
+		let (lead, bytes) = match field.split_first() {
+			Some((lead, bytes)) => (lead & 0x3F, bytes),
+			None => return Ok(Some(0))
+		};
+		
+		// Decode the number and check if it fits into an `u64`
+		let mut num: u64 = 0;
+		for byte in iter::once(lead).chain(bytes.iter().copied()) {
+			num = num.checked_mul(256)
+				.ok_or(BasicTarError::Unsupported("Base-256 number is too large"))?
+				| byte as u64;
+		}
+		Ok(Some(num))
+	}
+	fn into_base256_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		// Get the big-endian bytes and check if they fit into the field
+		let num = self.unwrap_or_default().to_be_bytes();
+		let available = field.len().saturating_sub(1);
+		let skip = num.iter().position(|b| *b != 0).unwrap_or(num.len());
+		if num.len() - skip > available {
+			let needed = num.len() - skip + 1;
+			Err(BasicTarError::FieldTooSmall{ needed, available: field.len() })?
+		}
+		
+		// Write the marker and the zero-padded number
+		let (padding, digits) = field.split_at_mut(field.len() - (num.len() - skip));
+		padding.iter_mut().for_each(|byte| *byte = 0);
+		padding[0] = 0x80;
+		digits.copy_from_slice(&num[skip..]);
+		Ok(())
+	}
 }
 impl U64Ext for u64 {
 	fn from_octal_field(field: &[u8]) -> Result<Self, BasicTarError> {
@@ -146,6 +650,17 @@ impl U64Ext for u64 {
 	fn into_octal_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
 		Some(self).into_octal_field(field)
 	}
+	fn into_numeric_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		Some(self).into_numeric_field(field)
+	}
+	
+	fn from_base256_field(field: &[u8]) -> Result<Self, BasicTarError> {
+		Option::from_base256_field(field)?
+			.ok_or(BasicTarError::InvalidData("Required field is empty"))
+	}
+	fn into_base256_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		Some(self).into_base256_field(field)
+	}
 }
 
 
@@ -153,6 +668,9 @@ impl U64Ext for u64 {
 pub(in crate::header) trait StringExt: Sized {
 	/// Creates a new string from a (potentially NUL-terminated) tar field
 	fn from_field(field: &[u8]) -> Result<Self, BasicTarError>;
+	/// Creates a new string from a (potentially NUL-terminated) tar field and replaces invalid
+	/// UTF-8 sequences with `U+FFFD`
+	fn from_field_lossy(field: &[u8]) -> Self;
 	/// Writes `self` to `field` and NUL-pads the string is field is longer than the value
 	fn into_field(self, field: &mut[u8]) -> Result<(), BasicTarError>;
 	
@@ -178,26 +696,27 @@ impl StringExt for Option<String> {
 			}
 		}
 	}
-	fn into_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
-		// Check if we can write the field
-		if field.len() < self.as_ref().map(|string| string.len()).unwrap_or(0) {
-			Err(BasicTarError::ApiMisuse("`field` is too small to hold the value"))?
+	fn from_field_lossy(field: &[u8]) -> Self {
+		let nul = field.iter().position(|b| *b == 0x00).unwrap_or(field.len());
+		match nul {
+			0 => None,
+			_ => Some(String::from_utf8_lossy(&field[..nul]).into_owned())
 		}
-		let string = self.as_ref().map(|string| string.as_str()).unwrap_or_default();
-		
-		// NUL-terminate the string and copy it to the field
-		let nul_terminated = string.bytes().chain(iter::repeat(0));
-		field.iter_mut().zip(nul_terminated).for_each(|(field, byte)| *field = byte);
-		Ok(())
+	}
+	fn into_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		self.as_deref().unwrap_or_default().write_field(field)
 	}
 	fn from_terminated_field(field: &[u8]) -> Result<Self, BasicTarError> {
 		let space = field.iter().position(|b| *b == b' ').unwrap_or(field.len());
 		Self::from_field(&field[..space])
 	}
 	fn into_terminated_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
-		// Get the index of the last byte
-		let last = field.len().checked_sub(1)
-			.ok_or(BasicTarError::ApiMisuse("`field` is too small to hold the value"))?;
+		// Check if we can write the value and the terminator and get the index of the last byte
+		let needed = self.as_ref().map(|string| string.len()).unwrap_or_default() + 1;
+		if field.len() < needed {
+			Err(BasicTarError::FieldTooSmall{ needed, available: field.len() })?
+		}
+		let last = field.len() - 1;
 		
 		// Write the value and set the last byte
 		self.into_field(&mut field[..last])?;
@@ -210,6 +729,9 @@ impl StringExt for String {
 		Option::<String>::from_field(field)?
 			.ok_or(BasicTarError::InvalidData("Required field is empty"))
 	}
+	fn from_field_lossy(field: &[u8]) -> Self {
+		Option::<String>::from_field_lossy(field).unwrap_or_default()
+	}
 	fn into_field(self, field: &mut[u8]) -> Result<(), BasicTarError> {
 		Some(self).into_field(field)
 	}
@@ -221,3 +743,23 @@ impl StringExt for String {
 		Some(self).into_terminated_field(field)
 	}
 }
+
+
+/// An extension for `str`
+pub(in crate::header) trait StrExt {
+	/// Writes `self` to `field` and NUL-pads the string if field is longer than the value
+	fn write_field(&self, field: &mut[u8]) -> Result<(), BasicTarError>;
+}
+impl StrExt for str {
+	fn write_field(&self, field: &mut[u8]) -> Result<(), BasicTarError> {
+		// Check if we can write the field
+		if field.len() < self.len() {
+			Err(BasicTarError::FieldTooSmall{ needed: self.len(), available: field.len() })?
+		}
+		
+		// NUL-terminate the string and copy it to the field
+		let nul_terminated = self.bytes().chain(iter::repeat(0));
+		field.iter_mut().zip(nul_terminated).for_each(|(field, byte)| *field = byte);
+		Ok(())
+	}
+}
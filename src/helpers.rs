@@ -1,8 +1,8 @@
 use std::{
 	cmp::min,
 	io::{
-		self, Read, Write,
-		ErrorKind::{ Interrupted, UnexpectedEof, WriteZero }
+		self, BufRead, Read, Write,
+		ErrorKind::{ Interrupted, InvalidInput, UnexpectedEof, WriteZero }
 	}
 };
 
@@ -15,42 +15,77 @@ pub trait ReadExt {
 	/// _Note: This function behaves like `read_exact`, except that you will never loose state in
 	/// case of an incomplete read - if the error is non-fatal (like `TimedOut`), you can always try
 	/// again later if nothing happened_
+	///
+	/// _Note: Errors other than `Interrupted` (e.g. `WouldBlock` from a non-blocking reader) are
+	/// returned as they are after all bytes read so far have been reported to `pos_cb`; to resume,
+	/// call this function again with the remaining part of `buf`_
 	fn try_read_exact(&mut self, buf: &mut[u8], pos_cb: impl FnMut(usize))
 		-> Result<(), io::Error>;
+	/// Tries to fill `buf` completely and calls the data callback `data_cb` with the freshly read
+	/// bytes on *every* successful `read` call (e.g. to feed a hasher incrementally)
+	///
+	/// _Note: This function behaves like `try_read_exact`, except that the callback receives the
+	/// read data instead of the amount of bytes read_
+	fn try_read_exact_cb(&mut self, buf: &mut[u8], data_cb: impl FnMut(&[u8]))
+		-> Result<(), io::Error>;
 	
 	/// Tries to consume `len` bytes and calls the position callback `pos_cb` with the amount of
 	/// bytes drained on *every* successful `read` call
 	///
 	/// _Note: This function behaves similar to `read_exact` (without buffer), except that you will
-	/// never loose state in case of an incomplete write - if the error is non-fatal (like
+	/// never loose state in case of an incomplete read - if the error is non-fatal (like
 	/// `TimedOut`), you can always try again later if nothing happened_
+	///
+	/// _Note: `pos_cb` is guaranteed to be called for every drained byte before an error is
+	/// returned, so if you sum up the reported lengths, you can resume by draining the remaining
+	/// bytes_
 	fn try_drain(&mut self, len: usize, pos_cb: impl FnMut(usize)) -> Result<(), io::Error>;
+	/// Tries to consume `len` bytes using `buf` as scratch buffer and calls the position callback
+	/// `pos_cb` with the amount of bytes drained on *every* successful `read` call
+	///
+	/// _Note: This function behaves like `try_drain`, except that the caller provides the scratch
+	/// buffer, so it can be reused across many calls (e.g. when skipping thousands of records); an
+	/// empty `buf` is rejected as `InvalidInput` if `len` is not zero_
+	fn try_drain_with_buf(&mut self, len: usize, buf: &mut[u8], pos_cb: impl FnMut(usize))
+		-> Result<(), io::Error>;
 }
 impl<T: Read> ReadExt for T {
-	fn try_read_exact(&mut self, mut buf: &mut[u8], mut pos_cb: impl FnMut(usize))
+	fn try_read_exact(&mut self, buf: &mut[u8], mut pos_cb: impl FnMut(usize))
+		-> Result<(), io::Error>
+	{
+		self.try_read_exact_cb(buf, |data| pos_cb(data.len()))
+	}
+	fn try_read_exact_cb(&mut self, mut buf: &mut[u8], mut data_cb: impl FnMut(&[u8]))
 		-> Result<(), io::Error>
 	{
 		'read_loop: while !buf.is_empty() {
-			match self.read(&mut buf) {
+			match self.read(buf) {
 				Err(ref e) if e.kind() == Interrupted => continue 'read_loop,
 				Err(e) => Err(e)?,
 				Ok(0) => Err(io::Error::from(UnexpectedEof))?,
 				Ok(len) => {
-					buf = &mut buf[len..];
-					pos_cb(len)
+					let (data, remaining) = buf.split_at_mut(len);
+					data_cb(data);
+					buf = remaining;
 				}
 			}
 		}
 		Ok(())
 	}
-	fn try_drain(&mut self, mut len: usize, mut pos_cb: impl FnMut(usize))
+	fn try_drain(&mut self, len: usize, pos_cb: impl FnMut(usize)) -> Result<(), io::Error> {
+		self.try_drain_with_buf(len, &mut[0; 4096], pos_cb)
+	}
+	fn try_drain_with_buf(&mut self, mut len: usize, buf: &mut[u8], mut pos_cb: impl FnMut(usize))
 		-> Result<(), io::Error>
 	{
+		if len > 0 && buf.is_empty() {
+			Err(io::Error::new(InvalidInput, "The scratch buffer is empty"))?
+		}
+		
 		// Read len bytes
 		while len > 0 {
-			// Create buffer and fill it
-			let buf = &mut[0; 4096][.. min(len, 4096)];
-			self.try_read_exact(buf, |read| {
+			let chunk = min(len, buf.len());
+			self.try_read_exact(&mut buf[..chunk], |read| {
 				len -= read;
 				pos_cb(read)
 			})?
@@ -60,6 +95,32 @@ impl<T: Read> ReadExt for T {
 }
 
 
+/// An extension for `BufRead`
+pub trait BufReadExt {
+	/// Consumes `len` bytes directly from the internal buffer without copying them into a scratch
+	/// buffer
+	///
+	/// _Note: This function behaves like `ReadExt::try_drain`, but uses `fill_buf`/`consume` and
+	/// is therefore considerably faster if you skip large records_
+	fn drain_buffered(&mut self, len: usize) -> Result<(), io::Error>;
+}
+impl<T: BufRead> BufReadExt for T {
+	fn drain_buffered(&mut self, mut len: usize) -> Result<(), io::Error> {
+		'drain_loop: while len > 0 {
+			let available = match self.fill_buf() {
+				Err(ref e) if e.kind() == Interrupted => continue 'drain_loop,
+				Err(e) => Err(e)?,
+				Ok([]) => Err(io::Error::from(UnexpectedEof))?,
+				Ok(buf) => min(buf.len(), len)
+			};
+			self.consume(available);
+			len -= available;
+		}
+		Ok(())
+	}
+}
+
+
 /// An extension for `Write`
 pub trait WriteExt {
 	/// Tries to write `data` completely and calls the position callback `pos_cb` with the amount of
@@ -78,13 +139,20 @@ pub trait WriteExt {
 	/// never loose state in case of an incomplete write - if the error is non-fatal (like
 	/// `TimedOut`), you can always try again later if nothing happened_
 	fn try_fill(&mut self, len: usize, counter: impl FnMut(usize)) -> Result<(), io::Error>;
+	/// Tries to write `len` copies of `byte` and calls the position callback `pos_cb` with the
+	/// amount of bytes written on *every* successful `write` call
+	///
+	/// _Note: This function behaves like `try_fill`, except that it writes `byte` instead of zeroes
+	/// (e.g. to make padding visible in a hex dump)_
+	fn try_fill_with(&mut self, len: usize, byte: u8, counter: impl FnMut(usize))
+		-> Result<(), io::Error>;
 }
 impl<T: Write> WriteExt for T {
 	fn try_write_exact(&mut self, mut data: &[u8], mut pos_cb: impl FnMut(usize))
 		-> Result<(), io::Error>
 	{
 		'write_loop: while !data.is_empty() {
-			match self.write(&data) {
+			match self.write(data) {
 				Err(ref e) if e.kind() == Interrupted => continue 'write_loop,
 				Err(e) => Err(e)?,
 				Ok(0) => Err(io::Error::from(WriteZero))?,
@@ -96,13 +164,16 @@ impl<T: Write> WriteExt for T {
 		}
 		Ok(())
 	}
-	fn try_fill(&mut self, mut len: usize, mut pos_cb: impl FnMut(usize))
+	fn try_fill(&mut self, len: usize, pos_cb: impl FnMut(usize)) -> Result<(), io::Error> {
+		self.try_fill_with(len, 0, pos_cb)
+	}
+	fn try_fill_with(&mut self, mut len: usize, byte: u8, mut pos_cb: impl FnMut(usize))
 		-> Result<(), io::Error>
 	{
-		// Write len zero bytes
+		// Write len bytes
 		while len > 0 {
 			// Create buffer and fill it
-			let buf = &mut[0; 4096][.. min(len, 4096)];
+			let buf = &mut[byte; 4096][.. min(len, 4096)];
 			self.try_write_exact(buf, |written| {
 				len -= written;
 				pos_cb(written)
@@ -117,6 +188,12 @@ impl<T: Write> WriteExt for T {
 pub trait U64Ext {
 	/// Ceils `self` to an integer multiple of `num`
 	fn ceil_to_multiple_of(&self, num: Self) -> Self;
+	/// Computes the amount of padding bytes necessary to pad `self` to an integer multiple of
+	/// `block`
+	///
+	/// _Note: `block` does not need to be the standard block size, so this also works for archives
+	/// with non-standard payload padding_
+	fn padding_to(&self, block: Self) -> Self;
 }
 impl U64Ext for u64 {
 	fn ceil_to_multiple_of(&self, num: Self) -> Self {
@@ -125,4 +202,7 @@ impl U64Ext for u64 {
 			r => *self + (num - r)
 		}
 	}
+	fn padding_to(&self, block: Self) -> Self {
+		self.ceil_to_multiple_of(block) - *self
+	}
 }
\ No newline at end of file
@@ -27,7 +27,7 @@
 //! 	stream.read_exact(&mut header_raw)?;
 //!
 //! 	// Parse the header and get the payload lengths
-//! 	let header = Header::parse(header_raw)?;
+//! 	let header = Header::parse(&header_raw)?;
 //! 	let payload_len = header.size;
 //! 	let payload_total_len = payload_len.ceil_to_multiple_of(BLOCK_LEN as u64);
 //!
@@ -64,24 +64,76 @@
 //!
 //! 	// Write the padding
 //! 	let payload_len = payload.len() as u64;
-//! 	let padding_len = payload_len.ceil_to_multiple_of(BLOCK_LEN as u64) - payload_len;
+//! 	let padding_len = payload_len.padding_to(BLOCK_LEN as u64);
 //! 	stream.try_fill(usize::try_from(padding_len)?, |_| {})?;
 //!
 //! 	Ok(())
 //! }
 //! ```
+//!
+//! ## Convenience functions
+//! If you don't need fine-grained control over the stream, you can also use
+//!  - [`read_record`](fn.read_record.html) and [`write_record`](fn.write_record.html) to read or
+//!    write a header, its payload and the padding at once
+//!  - [`skip_record`](fn.skip_record.html) to skip a payload without allocating it
+//!  - a [`PayloadReader`](struct.PayloadReader.html) to stream a payload
+//!  - [`write_eof`](fn.write_eof.html) to terminate an archive
+//!  - an [`ArchiveReader`](struct.ArchiveReader.html) to iterate over all records in a stream
+//!  - an [`ArchiveWriter`](struct.ArchiveWriter.html) to create an archive
+//!  - [`parse_archive`](fn.parse_archive.html) to parse an entire in-memory archive at once
+//!  - [`verify_archive`](fn.verify_archive.html) to check whether an archive stream is structurally
+//!    sound without extracting it
+//!  - [`index_archive`](fn.index_archive.html) to get the offsets of all records for random access
+//!  - a [`MultiVolumeReader`](struct.MultiVolumeReader.html) to read GNU multi-volume archives
+//!
+//! ## Features
+//!  - `std` (default): Enables the I/O helpers, records and archives; without it, only `Header`,
+//!    `raw` and the field helpers are available (`no_std` + `alloc`)
+//!  - `serde`: Derives `serde::Serialize` and `serde::Deserialize` for `Header`, `RecordType` and
+//!    `Format`
+//!  - `tokio`: Adds `AsyncReadExt` and `AsyncWriteExt`, async versions of `ReadExt` and `WriteExt`
+//!    for `tokio::io::AsyncRead` and `tokio::io::AsyncWrite`
+//!  - `tar-interop`: Adds conversions between `Header` and
+//!    [`tar::Header`](https://docs.rs/tar/latest/tar/struct.Header.html)
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::tabs_in_doc_comments)]
+
+extern crate alloc;
 
+#[cfg(feature = "std")]
+mod archive;
+#[cfg(feature = "tokio")]
+mod async_helpers;
 mod header;
+#[cfg(feature = "std")]
 mod helpers;
+#[cfg(feature = "std")]
+mod multivolume;
+#[cfg(feature = "std")]
+mod record;
+#[cfg(feature = "tar-interop")]
+mod tar_interop;
 
-use std::{
-	error::Error,
-	fmt::{ self, Display, Formatter }
-};
+use core::fmt::{ self, Display, Formatter };
+#[cfg(feature = "std")]
+use std::{ error::Error, io };
+pub use crate::header::{ Format, Header, HeaderBuilder, Mode, RecordType, raw };
+#[cfg(feature = "std")]
 pub use crate::{
-	header::{ Header, raw },
-	helpers::{ ReadExt, WriteExt, U64Ext }
+	archive::{
+		index_archive, parse_archive, verify_archive,
+		ArchiveReader, ArchiveWriter, FilterPaths, RecordIndex
+	},
+	multivolume::MultiVolumeReader,
+	helpers::{ BufReadExt, ReadExt, WriteExt, U64Ext },
+	record::{
+		align_archive_len, pad_to_block, read_record, read_record_at, skip_record, write_record,
+		write_eof, write_trailer, PayloadReader
+	}
 };
+#[cfg(feature = "tokio")]
+pub use crate::async_helpers::{ AsyncReadExt, AsyncWriteExt };
 
 
 /// A `basic_tar`-related error
@@ -93,12 +145,38 @@ pub enum BasicTarError {
 	InvalidData(&'static str),
 	/// The tar header field might be valid but contains an unsupported value
 	Unsupported(&'static str),
-	/// An empty (all zero) header was found (which is usually part of an end of archive indicator)
-	EmptyHeader
+	/// A value does not fit into its header field
+	FieldTooSmall {
+		/// The amount of bytes needed to hold the value
+		needed: usize,
+		/// The amount of bytes available in the field
+		available: usize
+	},
+	/// The stream ended within the payload or the padding of a record (a stream that ends within a
+	/// header is reported as `Io(UnexpectedEof)` instead)
+	Truncated {
+		/// The amount of payload and padding bytes of the record
+		expected: u64,
+		/// The amount of payload and padding bytes that were available
+		got: u64
+	},
+	/// An empty (all zero or all space) header was found (which is usually part of an end of
+	/// archive indicator)
+	EmptyHeader,
+	/// An I/O error occurred
+	#[cfg(feature = "std")]
+	Io(io::ErrorKind)
 }
 impl Display for BasicTarError {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		write!(f, "{:?}", self)
 	}
 }
-impl Error for BasicTarError {}
\ No newline at end of file
+#[cfg(feature = "std")]
+impl Error for BasicTarError {}
+#[cfg(feature = "std")]
+impl From<io::Error> for BasicTarError {
+	fn from(error: io::Error) -> Self {
+		BasicTarError::Io(error.kind())
+	}
+}
\ No newline at end of file
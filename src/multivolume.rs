@@ -0,0 +1,136 @@
+use crate::{
+	BasicTarError, Header, record,
+	header::raw::{ self, gnu_multivol, BLOCK_LEN, TypeFlag },
+	helpers::{ ReadExt, U64Ext }
+};
+use std::io::{ self, Read };
+
+
+/// An iterator over the records in a GNU multi-volume archive that reassembles payloads which span
+/// volume boundaries
+///
+/// If the current volume is exhausted, `next_volume` is called to open the next volume. Volume
+/// headers are skipped and continuation records are validated against the interrupted record.
+///
+/// _Note: GNU long name and pax records are returned as they are; the iterator stops after the end
+/// of archive indicator (two empty headers) or after the first error_
+pub struct MultiVolumeReader<R: Read, F: FnMut() -> io::Result<R>> {
+	reader: R,
+	next_volume: F,
+	empty_headers: usize,
+	done: bool
+}
+impl<R: Read, F: FnMut() -> io::Result<R>> MultiVolumeReader<R, F> {
+	/// Creates a new multi-volume reader over the first volume `reader` that calls `next_volume`
+	/// to open the subsequent volumes
+	pub fn new(reader: R, next_volume: F) -> Self {
+		Self{ reader, next_volume, empty_headers: 0, done: false }
+	}
+	
+	/// Reads the next raw header block or returns `None` if the current volume is exhausted
+	fn read_raw(&mut self) -> Result<Option<raw::header::Raw>, BasicTarError> {
+		let mut header_raw = raw::header::raw();
+		let mut read = 0;
+		match self.reader.try_read_exact(&mut header_raw, |len| read += len) {
+			Ok(_) => Ok(Some(header_raw)),
+			Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof && read == 0 => Ok(None),
+			Err(e) => Err(e)?
+		}
+	}
+	
+	/// Reads the payload and the padding of `header` and continues on the next volume if necessary
+	fn read_payload(&mut self, header: &Header) -> Result<Vec<u8>, BasicTarError> {
+		let mut payload = vec![0; header.payload_len_checked()?];
+		let mut pos = 0;
+		while pos < payload.len() {
+			match self.reader.try_read_exact(&mut payload[pos..], |read| pos += read) {
+				Ok(_) => (),
+				Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof =>
+					self.continue_volume(header, pos as u64)?,
+				Err(e) => Err(e)?
+			}
+		}
+		
+		// Drain the padding (volumes are a multiple of the block size, so it never spans volumes)
+		let padding_len = header.size.padding_to(BLOCK_LEN as u64) as usize;
+		self.reader.try_drain(padding_len, |_| {})?;
+		Ok(payload)
+	}
+	
+	/// Opens the next volume and validates the continuation record for `header` at `offset`
+	fn continue_volume(&mut self, header: &Header, offset: u64) -> Result<(), BasicTarError> {
+		self.reader = (self.next_volume)()?;
+		loop {
+			let header_raw = self.read_raw()?
+				.ok_or(BasicTarError::Io(io::ErrorKind::UnexpectedEof))?;
+			let continuation = Header::parse(&header_raw)?;
+			match continuation.typeflag {
+				TypeFlag::GNU_VOLHDR => record::skip_record(&mut self.reader, &continuation)?,
+				TypeFlag::GNU_MULTIVOL if continuation.path == header.path => {
+					let continued = gnu_multivol::offset(&header_raw)?;
+					return match continued == offset && continuation.size == header.size - offset {
+						true => Ok(()),
+						false => Err(BasicTarError::InvalidData("Invalid continuation record"))
+					}
+				},
+				_ => Err(BasicTarError::InvalidData("Missing multi-volume continuation record"))?
+			}
+		}
+	}
+	
+	/// Reads the next record and switches the volume if the current volume is exhausted
+	fn read_next(&mut self) -> Result<Option<(Header, Vec<u8>)>, BasicTarError> {
+		loop {
+			// Read the next header or open the next volume
+			let header_raw = match self.read_raw()? {
+				Some(header_raw) => header_raw,
+				None if self.empty_headers > 0 =>
+					Err(io::Error::from(io::ErrorKind::UnexpectedEof))?,
+				None => {
+					self.reader = (self.next_volume)()?;
+					continue
+				}
+			};
+			let header = match Header::parse(&header_raw) {
+				Err(BasicTarError::EmptyHeader) => return Ok(None),
+				header => header?
+			};
+			
+			// Skip volume headers and reject orphaned continuation records
+			match header.typeflag {
+				TypeFlag::GNU_VOLHDR => record::skip_record(&mut self.reader, &header)?,
+				TypeFlag::GNU_MULTIVOL =>
+					Err(BasicTarError::InvalidData("Unexpected multi-volume continuation record"))?,
+				_ => {
+					let payload = self.read_payload(&header)?;
+					return Ok(Some((header, payload)))
+				}
+			}
+		}
+	}
+}
+impl<R: Read, F: FnMut() -> io::Result<R>> Iterator for MultiVolumeReader<R, F> {
+	type Item = Result<(Header, Vec<u8>), BasicTarError>;
+	
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.done {
+			match self.read_next() {
+				Ok(Some(record)) => {
+					self.empty_headers = 0;
+					return Some(Ok(record))
+				},
+				Ok(None) => {
+					self.empty_headers += 1;
+					self.done = self.empty_headers >= 2;
+				},
+				Err(BasicTarError::Io(io::ErrorKind::UnexpectedEof)) if self.empty_headers > 0 =>
+					self.done = true,
+				Err(e) => {
+					self.done = true;
+					return Some(Err(e))
+				}
+			}
+		}
+		None
+	}
+}
@@ -0,0 +1,199 @@
+use crate::{
+	BasicTarError, Header,
+	header::raw::{ BLOCK_LEN, EOF_BLOCK },
+	helpers::{ ReadExt, WriteExt, U64Ext }
+};
+use std::{
+	cmp::{ max, min },
+	io::{ self, Read, Seek, SeekFrom, Write, ErrorKind::UnexpectedEof }
+};
+
+
+/// Reads the next record from `stream` or returns `None` if an empty header is found (which is
+/// usually part of an end of archive indicator)
+pub fn read_record(mut stream: impl Read) -> Result<Option<(Header, Vec<u8>)>, BasicTarError> {
+	match read_header(&mut stream)? {
+		Some(header) => {
+			let payload = read_payload(&mut stream, &header)?;
+			Ok(Some((header, payload)))
+		},
+		None => Ok(None)
+	}
+}
+/// Seeks to `header_offset` in `stream` and reads the record at this position (e.g. with an offset
+/// from `index_archive`)
+///
+/// _Note: an empty header at `header_offset` is reported as `BasicTarError::EmptyHeader`_
+pub fn read_record_at(mut stream: impl Read + Seek, header_offset: u64)
+	-> Result<(Header, Vec<u8>), BasicTarError>
+{
+	stream.seek(SeekFrom::Start(header_offset))?;
+	read_record(stream)?.ok_or(BasicTarError::EmptyHeader)
+}
+/// Reads the next header from `stream` or returns `None` if an empty header is found
+pub(crate) fn read_header(stream: impl Read) -> Result<Option<Header>, BasicTarError> {
+	match Header::parse_from_reader(stream) {
+		Err(BasicTarError::EmptyHeader) => Ok(None),
+		header => Ok(Some(header?))
+	}
+}
+/// Reads the payload described by `header` from `stream` and drains the padding
+pub(crate) fn read_payload(mut stream: impl Read, header: &Header)
+	-> Result<Vec<u8>, BasicTarError>
+{
+	// Get the payload lengths
+	let payload_len = header.payload_len_checked()?;
+	let padding_len = header.size.padding_to(BLOCK_LEN as u64) as usize;
+	let (expected, mut got) = (header.size + padding_len as u64, 0);
+	
+	// Read the payload
+	let mut payload = vec![0; payload_len];
+	stream.try_read_exact(&mut payload, |read| got += read as u64)
+		.map_err(|e| truncated(e, expected, got))?;
+	
+	// Drain the padding and return the payload
+	stream.try_drain(padding_len, |drained| got += drained as u64)
+		.map_err(|e| truncated(e, expected, got))?;
+	Ok(payload)
+}
+/// Converts an `UnexpectedEof` within the payload or padding of a record into
+/// `BasicTarError::Truncated`
+pub(crate) fn truncated(error: io::Error, expected: u64, got: u64) -> BasicTarError {
+	match error.kind() {
+		UnexpectedEof => BasicTarError::Truncated{ expected, got },
+		kind => BasicTarError::Io(kind)
+	}
+}
+
+
+/// Skips the payload and the padding of the record described by `header` in `stream` without
+/// allocating a buffer
+pub fn skip_record(mut stream: impl Read, header: &Header) -> Result<(), BasicTarError> {
+	// Drain the payload and the padding in `usize`-sized chunks
+	let (expected, mut got) = (header.size.ceil_to_multiple_of(BLOCK_LEN as u64), 0);
+	while got < expected {
+		let chunk = min(expected - got, usize::MAX as u64) as usize;
+		stream.try_drain(chunk, |drained| got += drained as u64)
+			.map_err(|e| truncated(e, expected, got))?;
+	}
+	Ok(())
+}
+
+
+/// Writes `header`, `payload` and the padding to `stream`
+pub fn write_record(header: Header, payload: &[u8], mut stream: impl Write)
+	-> Result<(), BasicTarError>
+{
+	// Write the header and the payload
+	header.write_to(&mut stream)?;
+	stream.try_write_exact(payload, |_| {})?;
+	
+	// Write the padding
+	let padding_len = (payload.len() as u64).padding_to(BLOCK_LEN as u64) as usize;
+	stream.try_fill(padding_len, |_| {})?;
+	Ok(())
+}
+
+
+/// Extends `buf` with zeroes up to the next multiple of the block size and returns the amount of
+/// bytes added
+pub fn pad_to_block(buf: &mut Vec<u8>) -> usize {
+	let padding_len = (buf.len() as u64).padding_to(BLOCK_LEN as u64) as usize;
+	buf.resize(buf.len() + padding_len, 0);
+	padding_len
+}
+
+
+/// Writes the end of archive indicator (two zero blocks) to `stream`
+pub fn write_eof(stream: impl Write) -> Result<(), BasicTarError> {
+	write_trailer(stream, 2)
+}
+/// Writes `blocks` zero blocks to `stream` (e.g. to pad the end of archive indicator to the
+/// blocking factor of a specific tool)
+///
+/// _Note: a valid end of archive indicator consists of at least two zero blocks_
+pub fn write_trailer(mut stream: impl Write, blocks: usize) -> Result<(), BasicTarError> {
+	for _ in 0..blocks {
+		stream.try_write_exact(&EOF_BLOCK, |_| {})?;
+	}
+	Ok(())
+}
+
+/// Computes the length of an archive of `current_len` bytes after padding it to a multiple of
+/// `blocking_factor` blocks (GNU tar uses a blocking factor of 20, i.e. 10240 bytes)
+///
+/// _Note: `current_len` should include the end of archive indicator; a blocking factor of zero is
+/// treated as one_
+pub fn align_archive_len(current_len: u64, blocking_factor: usize) -> u64 {
+	let record_len = BLOCK_LEN as u64 * max(blocking_factor, 1) as u64;
+	current_len.ceil_to_multiple_of(record_len)
+}
+
+
+/// A reader that yields exactly the payload of a record and drains the padding afterwards
+///
+/// _Note: if the reader is dropped without calling `finish`, the remaining payload and padding are
+/// drained during `drop` and any error that occurs is swallowed; call `finish` to handle errors_
+pub struct PayloadReader<'a, R: Read> {
+	stream: &'a mut R,
+	len: u64,
+	remaining: u64,
+	padding: u64,
+	finished: bool
+}
+impl<'a, R: Read> PayloadReader<'a, R> {
+	/// Creates a new payload reader over `stream` for the record described by `header`
+	pub fn new(stream: &'a mut R, header: &Header) -> Self {
+		let padding = header.size.padding_to(BLOCK_LEN as u64);
+		Self{ stream, len: header.size + padding, remaining: header.size, padding, finished: false }
+	}
+	
+	/// The amount of payload bytes that have not been read yet
+	pub fn remaining(&self) -> u64 {
+		self.remaining
+	}
+	
+	/// Drains the remaining payload and the padding
+	pub fn finish(mut self) -> Result<(), BasicTarError> {
+		self.finished = true;
+		self.drain()
+	}
+	
+	/// Drains the remaining payload and the padding in `usize`-sized chunks
+	fn drain(&mut self) -> Result<(), BasicTarError> {
+		let (len, mut remaining) = (self.len, self.remaining + self.padding);
+		while remaining > 0 {
+			let chunk = min(remaining, usize::MAX as u64) as usize;
+			self.stream.try_drain(chunk, |drained| remaining -= drained as u64)
+				.map_err(|e| truncated(e, len, len - remaining))?;
+		}
+		self.remaining = 0;
+		self.padding = 0;
+		Ok(())
+	}
+}
+impl<R: Read> Read for PayloadReader<'_, R> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		// Limit the buffer to the remaining payload
+		let len = min(buf.len() as u64, self.remaining) as usize;
+		if len == 0 {
+			return Ok(0)
+		}
+		
+		// Read the payload
+		match self.stream.read(&mut buf[..len])? {
+			0 => Err(io::Error::from(UnexpectedEof)),
+			read => {
+				self.remaining -= read as u64;
+				Ok(read)
+			}
+		}
+	}
+}
+impl<R: Read> Drop for PayloadReader<'_, R> {
+	fn drop(&mut self) {
+		if !self.finished {
+			let _ = self.drain();
+		}
+	}
+}
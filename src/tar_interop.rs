@@ -0,0 +1,26 @@
+use crate::{ BasicTarError, Header };
+use std::convert::TryFrom;
+
+
+impl TryFrom<&tar::Header> for Header {
+	type Error = BasicTarError;
+	
+	/// Parses the raw bytes of a `tar::Header`
+	///
+	/// _Note: like `Header::parse`, this verifies the checksum (see `tar::Header::set_cksum`) and
+	/// fails if a field is invalid or not UTF-8_
+	fn try_from(header: &tar::Header) -> Result<Self, Self::Error> {
+		Header::parse_slice(header.as_bytes())
+	}
+}
+impl TryFrom<&Header> for tar::Header {
+	type Error = BasicTarError;
+	
+	/// Serializes `header` into a `tar::Header`
+	///
+	/// _Note: this fails if `header` cannot be serialized (see `Header::serialize`)_
+	fn try_from(header: &Header) -> Result<Self, Self::Error> {
+		let header_raw = header.serialize()?;
+		Ok(tar::Header::from_byte_slice(&header_raw).clone())
+	}
+}
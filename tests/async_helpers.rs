@@ -0,0 +1,68 @@
+#![cfg(feature = "tokio")]
+
+use basic_tar::{
+	AsyncReadExt, AsyncWriteExt, Header, U64Ext,
+	raw::{ self, BLOCK_LEN }
+};
+
+
+#[tokio::test]
+async fn test_async_read() {
+	let mut stream = include_bytes!("predefined_nul.tar") as &[u8];
+	
+	// Read the first header
+	let (mut header_raw, mut read) = (raw::header::raw(), 0);
+	stream.try_read_exact(&mut header_raw, |len| read += len).await.unwrap();
+	assert_eq!(read, BLOCK_LEN);
+	assert_eq!(Header::parse(&header_raw).unwrap().path, "predefined_0.plain");
+	
+	// Drain the first payload and read the second header
+	let mut drained = 0;
+	stream.try_drain(BLOCK_LEN, |len| drained += len).await.unwrap();
+	assert_eq!(drained, BLOCK_LEN);
+	
+	stream.try_read_exact(&mut header_raw, |_| {}).await.unwrap();
+	assert_eq!(Header::parse(&header_raw).unwrap().path, "predefined_1.plain");
+	
+	// Drain beyond the end of the stream
+	let err = stream.try_drain(BLOCK_LEN * 4, |_| {}).await.unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+
+#[tokio::test]
+async fn test_async_roundtrip() {
+	let (mut writer, mut reader) = tokio::io::duplex(64);
+	let header = Header::builder("predefined_0.plain")
+		.mode(0o644).uid(0o765).gid(0o24)
+		.size(0o11).mtime(0o13521071532)
+		.build();
+	let payload = include_bytes!("predefined_0.plain");
+	
+	// Write the record
+	let header_raw = header.serialize().unwrap();
+	let write = async move {
+		let mut written = 0;
+		writer.try_write_exact(&header_raw, |len| written += len).await.unwrap();
+		writer.try_write_exact(payload, |len| written += len).await.unwrap();
+		writer.try_fill(BLOCK_LEN - payload.len(), |len| written += len).await.unwrap();
+		assert_eq!(written, BLOCK_LEN * 2);
+	};
+	
+	// Read the record
+	let read = async move {
+		let mut header_raw = raw::header::raw();
+		reader.try_read_exact(&mut header_raw, |_| {}).await.unwrap();
+		let header = Header::parse(&header_raw).unwrap();
+		
+		let mut payload = vec![0; header.size as usize];
+		reader.try_read_exact(&mut payload, |_| {}).await.unwrap();
+		let padding_len = header.size.ceil_to_multiple_of(BLOCK_LEN as u64) - header.size;
+		reader.try_drain(padding_len as usize, |_| {}).await.unwrap();
+		(header, payload)
+	};
+	
+	let (_, (_header, _payload)) = tokio::join!(write, read);
+	assert_eq!(_header, header);
+	assert_eq!(_payload.as_slice(), payload);
+}
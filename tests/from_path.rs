@@ -0,0 +1,50 @@
+#![cfg(feature = "std")]
+
+use basic_tar::{ Header, raw::TypeFlag };
+use std::{ env, fs, path::PathBuf, time::UNIX_EPOCH };
+
+
+/// Creates a new empty test directory
+fn test_dir(name: &str) -> PathBuf {
+	let dir = env::temp_dir().join(format!("basic_tar_{}_{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+
+#[test]
+fn test_from_path() {
+	let dir = test_dir("from_path");
+	
+	// Test a regular file
+	let file = dir.join("predefined_0.plain");
+	fs::write(&file, include_bytes!("predefined_0.plain")).unwrap();
+	let header = Header::from_path(&file).unwrap();
+	assert_eq!(header.path, file.to_str().unwrap());
+	assert_eq!(header.typeflag, TypeFlag::REGULAR);
+	assert_eq!(header.size, 9);
+	
+	let modified = fs::metadata(&file).unwrap().modified().unwrap();
+	assert_eq!(header.mtime, Some(modified.duration_since(UNIX_EPOCH).unwrap().as_secs()));
+	#[cfg(unix)]
+	assert!(header.mode.is_some() && header.uid.is_some() && header.gid.is_some());
+	#[cfg(not(unix))]
+	assert_eq!(header.mode, None);
+	
+	// Test a directory
+	let header = Header::from_path(&dir).unwrap();
+	assert_eq!(header.typeflag, TypeFlag::DIRECTORY);
+	assert_eq!(header.size, 0);
+	
+	// Test a symlink
+	#[cfg(unix)] {
+		let link = dir.join("link");
+		std::os::unix::fs::symlink("predefined_0.plain", &link).unwrap();
+		let header = Header::from_path(&link).unwrap();
+		assert_eq!(header.typeflag, TypeFlag::SYMLINK);
+		assert_eq!(header.linkname.as_deref(), Some("predefined_0.plain"));
+	}
+	
+	fs::remove_dir_all(&dir).unwrap();
+}
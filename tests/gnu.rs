@@ -0,0 +1,264 @@
+#![cfg(feature = "std")]
+
+use basic_tar::{
+	ArchiveReader, ArchiveWriter, BasicTarError, Header, MultiVolumeReader,
+	raw::{ self, TypeFlag, BLOCK_LEN }
+};
+use std::io::{ self, Cursor };
+
+
+#[test]
+fn test_long_name() {
+	let archive = include_bytes!("predefined_gnu.tar");
+	let mut records = ArchiveReader::new(Cursor::new(archive as &[u8]));
+	
+	let (header, payload) = records.next().unwrap().unwrap();
+	assert_eq!(header, Header {
+		path: format!("{}name.plain", "long_".repeat(38)),
+		mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
+		size: 0o11, mtime: Some(0o13521657412),
+		typeflag: TypeFlag::REGULAR, linkname: None,
+		uname: Some("keziah".into()), gname: Some("staff".into()),
+		magic: Some(*b"ustar "), version: Some(*b" \0"),
+		..Default::default()
+	});
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_0.plain"));
+	assert!(records.next().is_none());
+}
+
+
+#[test]
+fn test_long_name_and_link() {
+	let archive = include_bytes!("predefined_gnu_link.tar");
+	let mut records = ArchiveReader::new(Cursor::new(archive as &[u8]));
+	
+	let (header, payload) = records.next().unwrap().unwrap();
+	assert_eq!(header, Header {
+		path: format!("{}link.plain", "long_".repeat(38)),
+		mode: Some(0o777), uid: Some(0o765), gid: Some(0o24),
+		size: 0, mtime: Some(0o13521657412),
+		typeflag: TypeFlag::SYMLINK, linkname: Some(format!("{}0.plain", "target_".repeat(20))),
+		uname: Some("keziah".into()), gname: Some("staff".into()),
+		magic: Some(*b"ustar "), version: Some(*b" \0"),
+		..Default::default()
+	});
+	assert!(payload.is_empty());
+	assert!(records.next().is_none());
+}
+
+
+#[test]
+fn test_sparse() {
+	let archive = include_bytes!("predefined_sparse.tar");
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&archive[..BLOCK_LEN]);
+	
+	let header = Header::parse(&header_raw).unwrap();
+	assert_eq!(header.typeflag, TypeFlag::GNU_SPARSE);
+	assert_eq!(header.size, 4102);
+	
+	let map = raw::gnu_sparse::parse(&header_raw).unwrap();
+	assert_eq!(map, [(8192, 4096), (65536, 6), (65542, 0)]);
+	assert_eq!(raw::gnu_sparse::real_size(&header_raw), Ok(65542));
+	
+	// Set the `isextended`-flag
+	header_raw[482] = 1;
+	assert_eq!(
+		raw::gnu_sparse::parse(&header_raw),
+		Err(BasicTarError::Unsupported("GNU sparse continuation blocks are not supported"))
+	);
+}
+
+
+#[test]
+fn test_sparse_strict_typeflags() {
+	let archive = include_bytes!("predefined_sparse.tar");
+	
+	// Sparse records are returned as they are by default ...
+	let mut reader = ArchiveReader::new(Cursor::new(archive as &[u8]));
+	assert_eq!(reader.next().unwrap().unwrap().0.typeflag, TypeFlag::GNU_SPARSE);
+	
+	// ... and rejected in strict mode
+	let mut reader = ArchiveReader::new(Cursor::new(archive as &[u8])).strict_typeflags(true);
+	assert_eq!(
+		reader.next(),
+		Some(Err(BasicTarError::Unsupported("Unsupported type flag: GNU sparse file")))
+	);
+	assert!(reader.next().is_none());
+}
+
+
+#[test]
+fn test_atime_ctime() {
+	// Create a GNU header like `tar --incremental` does, with times in place of the USTAR prefix
+	let archive = include_bytes!("predefined_gnu.tar");
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&archive[BLOCK_LEN * 2..BLOCK_LEN * 3]);
+	header_raw[345..357].copy_from_slice(b"13521657413\0");
+	header_raw[357..369].copy_from_slice(b"13521657414\0");
+	raw::rewrite_checksum(&mut header_raw);
+	
+	// The times are parsed and do not end up in the path
+	let header = Header::parse(&header_raw).unwrap();
+	assert_eq!(header.path, "long_".repeat(20));
+	assert_eq!(header.atime, Some(0o13521657413));
+	assert_eq!(header.ctime, Some(0o13521657414));
+	assert_eq!(header.serialize().unwrap()[..], header_raw[..]);
+	
+	// Unset times are left empty
+	let header = Header{ atime: None, ctime: None, ..header };
+	assert!(header.serialize().unwrap()[345..369].iter().all(|b| *b == 0));
+	
+	// Non-GNU headers neither parse nor serialize the times
+	let ustar = Header {
+		magic: Some(*b"ustar\0"), version: Some(*b"00"), atime: Some(1), ctime: Some(2),
+		..header.clone()
+	};
+	let ustar = Header::parse(&ustar.serialize().unwrap()).unwrap();
+	assert_eq!((ustar.atime, ustar.ctime), (None, None));
+	
+	// GNU headers have no prefix, so long paths need a GNU long name record
+	let error = Err(BasicTarError::Unsupported("Path too long for GNU name field"));
+	let name = "long_".repeat(20);
+	let long_path = format!("2024/{}", name);
+	let gnu = Header{ path: long_path.clone(), ..header };
+	assert_eq!(gnu.validate(), error);
+	assert_eq!(gnu.serialize().map(|_| ()), error);
+	assert_eq!(ArchiveWriter::new(Vec::new()).append(gnu.clone(), b"").map(|_| ()), error);
+	
+	let mut writer = ArchiveWriter::new(Vec::new()).gnu_long_names(true);
+	writer.append(gnu.clone(), b"").unwrap();
+	let archive = writer.finish().unwrap();
+	let mut records = ArchiveReader::new(Cursor::new(archive));
+	assert_eq!(records.next().unwrap().unwrap().0, gnu);
+	
+	// The bytes after the link name are always the access and change time of GNU headers
+	let mut gnu_raw = header_raw;
+	gnu_raw[345..369].copy_from_slice(&[0; 24]);
+	gnu_raw[345..350].copy_from_slice(b"2024\0");
+	raw::rewrite_checksum(&mut gnu_raw);
+	let parsed = Header::parse(&gnu_raw).unwrap();
+	assert_eq!((parsed.path.as_str(), parsed.atime), (name.as_str(), Some(0o2024)));
+	assert_eq!(Header::parse_raw_fields(&gnu_raw).unwrap().0, name.as_bytes());
+	
+	gnu_raw[345..350].copy_from_slice(b"dir_\0");
+	raw::rewrite_checksum(&mut gnu_raw);
+	assert_eq!(Header::parse(&gnu_raw), Err(BasicTarError::InvalidData("Invalid octal number")));
+	
+	// This also applies once a time is set
+	let mut gnu = Header{ atime: Some(1), ..gnu };
+	assert_eq!(gnu.validate(), error);
+	assert_eq!(gnu.serialize().map(|_| ()), error);
+	assert_eq!(gnu.set_path(long_path), error);
+	gnu.set_path(name).unwrap();
+	assert_eq!(Header::parse(&gnu.serialize().unwrap()).unwrap().atime, Some(1));
+}
+
+
+#[test]
+fn test_checksum_layout() {
+	// Reserialize the GNU header and compare it against the GNU tar output
+	let archive = include_bytes!("predefined_gnu.tar");
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&archive[BLOCK_LEN * 2..BLOCK_LEN * 3]);
+	
+	let header = Header::parse(&header_raw).unwrap();
+	let serialized = header.serialize().unwrap();
+	assert_eq!(&serialized[148..156], b"034670\0 ");
+	assert_eq!(&serialized[..], &header_raw[..]);
+}
+
+
+#[test]
+fn test_volume_header() {
+	let archive = include_bytes!("predefined_volume.tar");
+	let mut records = ArchiveReader::new(Cursor::new(archive as &[u8]));
+	
+	// The volume header names the volume and has an empty size field
+	let (header, payload) = records.next().unwrap().unwrap();
+	assert!(header.is_volume_header());
+	assert_eq!(header.path, "predefined volume");
+	assert_eq!(header.size, 0);
+	assert!(payload.is_empty());
+	assert!(header.validate().is_ok());
+	
+	let (header, payload) = records.next().unwrap().unwrap();
+	assert!(!header.is_volume_header());
+	assert_eq!(header.path, "predefined_0.plain");
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_0.plain"));
+	assert!(records.next().is_none());
+}
+
+
+#[test]
+fn test_multi_volume() {
+	let mut volumes = vec![include_bytes!("predefined_multivol_1.tar") as &[u8]].into_iter();
+	let first = Cursor::new(include_bytes!("predefined_multivol_0.tar") as &[u8]);
+	let mut records = MultiVolumeReader::new(first, || {
+		volumes.next().map(Cursor::new).ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+	});
+	
+	// The payload spans both volumes
+	let (header, payload) = records.next().unwrap().unwrap();
+	assert_eq!(header.path, "predefined_2.plain");
+	assert_eq!(header.size, 15000);
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_2.plain") as &[u8]);
+	assert!(records.next().is_none());
+}
+
+
+#[test]
+fn test_multi_volume_mismatch() {
+	// Start with the second volume so that the continuation record is orphaned
+	let first = Cursor::new(include_bytes!("predefined_multivol_1.tar") as &[u8]);
+	let mut records = MultiVolumeReader::new(first, || -> io::Result<Cursor<&[u8]>> {
+		Err(io::Error::from(io::ErrorKind::NotFound))
+	});
+	assert_eq!(
+		records.next().unwrap(),
+		Err(BasicTarError::InvalidData("Unexpected multi-volume continuation record"))
+	);
+	
+	// Stop after the first volume
+	let first = Cursor::new(include_bytes!("predefined_multivol_0.tar") as &[u8]);
+	let mut records = MultiVolumeReader::new(first, || -> io::Result<Cursor<&[u8]>> {
+		Err(io::Error::from(io::ErrorKind::NotFound))
+	});
+	assert_eq!(records.next().unwrap(), Err(BasicTarError::Io(io::ErrorKind::NotFound)));
+}
+
+
+#[test]
+fn test_dumpdir() {
+	// The payload of a GNU incremental dump of a directory with a file and a subdirectory
+	let payload = b"Ypredefined_0.plain\0Dsub\0\0";
+	assert_eq!(
+		raw::gnu_dumpdir::parse(payload).unwrap(),
+		[(b'Y', "predefined_0.plain".to_string()), (b'D', "sub".to_string())]
+	);
+	assert!(raw::gnu_dumpdir::parse(b"\0").unwrap().is_empty());
+	assert_eq!(
+		raw::gnu_dumpdir::parse(b"Ysub"),
+		Err(BasicTarError::InvalidData("Dumpdir entry is not NUL-terminated"))
+	);
+}
+
+
+#[test]
+fn test_write_long_name_and_link() {
+	let archives = [
+		&include_bytes!("predefined_gnu.tar")[..],
+		&include_bytes!("predefined_gnu_link.tar")[..]
+	];
+	for archive in archives.iter().copied() {
+		// Copy the archive and compare it against the GNU tar output
+		let mut writer = ArchiveWriter::new(Vec::new()).gnu_long_names(true);
+		for record in ArchiveReader::new(Cursor::new(archive)) {
+			let (header, payload) = record.unwrap();
+			writer.append(header, &payload).unwrap();
+		}
+		let written = writer.finish().unwrap();
+		assert_eq!(written.as_slice(), &archive[..written.len()]);
+		assert!(archive[written.len()..].iter().all(|b| *b == 0));
+	}
+}
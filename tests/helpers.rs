@@ -0,0 +1,174 @@
+#![cfg(feature = "std")]
+
+use basic_tar::{ BufReadExt, ReadExt, U64Ext, WriteExt };
+use std::io::{ self, BufReader, Read };
+
+
+/// A reader that yields at most `chunk` bytes per `read` call
+struct ChunkedReader<'a> {
+	data: &'a [u8],
+	chunk: usize
+}
+impl Read for ChunkedReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		let len = self.chunk.min(buf.len());
+		self.data.read(&mut buf[..len])
+	}
+}
+
+
+#[test]
+fn test_try_read_exact_cb() {
+	let data = include_bytes!("predefined_0.macos");
+	let mut reader = ChunkedReader{ data, chunk: 100 };
+	
+	// Read the data and collect the chunks
+	let (mut buf, mut chunks) = (vec![0; data.len()], Vec::new());
+	reader.try_read_exact_cb(&mut buf, |chunk| chunks.push(chunk.to_vec())).unwrap();
+	assert_eq!(buf.as_slice(), data as &[u8]);
+	assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(), [100, 100, 100, 84]);
+	assert_eq!(chunks.concat().as_slice(), data as &[u8]);
+}
+
+
+#[test]
+fn test_padding_to() {
+	assert_eq!(0u64.padding_to(512), 0);
+	assert_eq!(1u64.padding_to(512), 511);
+	assert_eq!(511u64.padding_to(512), 1);
+	assert_eq!(512u64.padding_to(512), 0);
+	assert_eq!(513u64.padding_to(512), 511);
+}
+
+
+/// A reader that fails with `TimedOut` once after `timeout` bytes
+struct TimeoutReader<'a> {
+	data: &'a [u8],
+	timeout: Option<usize>
+}
+impl Read for TimeoutReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		match self.timeout {
+			Some(0) => {
+				self.timeout = None;
+				Err(io::Error::from(io::ErrorKind::TimedOut))
+			},
+			Some(ref mut timeout) => {
+				let len = buf.len().min(*timeout).min(100);
+				*timeout -= len;
+				self.data.read(&mut buf[..len])
+			},
+			None => self.data.read(buf)
+		}
+	}
+}
+
+
+#[test]
+fn test_try_drain_resume() {
+	let data = [0x2a; 10_000];
+	for timeout in [0, 1, 4095, 4096, 4097, 8999].iter() {
+		let mut reader = TimeoutReader{ data: &data, timeout: Some(*timeout) };
+		
+		// Drain until the timeout occurs
+		let mut drained = 0;
+		let err = reader.try_drain(9_000, |len| drained += len).unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+		assert_eq!(drained, *timeout);
+		
+		// Resume and verify that exactly 9000 bytes were consumed in total
+		reader.try_drain(9_000 - drained, |len| drained += len).unwrap();
+		assert_eq!(drained, 9_000);
+		assert_eq!(reader.data.len(), 1_000);
+	}
+}
+
+
+#[test]
+fn test_try_drain_with_buf() {
+	let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+	
+	// Draining with a small reusable buffer must be equivalent to `try_drain`
+	let (mut reader, mut drained) = (ChunkedReader{ data: &data, chunk: 700 }, Vec::new());
+	reader.try_drain(9_000, |len| drained.push(len)).unwrap();
+	
+	let (mut reader_buf, mut drained_buf) = (ChunkedReader{ data: &data, chunk: 700 }, 0);
+	let mut scratch = [0xff; 512];
+	for _ in 0..3 {
+		reader_buf.try_drain_with_buf(3_000, &mut scratch, |len| drained_buf += len).unwrap();
+	}
+	assert_eq!(drained.iter().sum::<usize>(), drained_buf);
+	assert_eq!(reader.data, reader_buf.data);
+	assert_eq!(reader_buf.data.len(), 1_000);
+	
+	// An empty scratch buffer is rejected
+	let err = reader_buf.try_drain_with_buf(1, &mut [], |_| {}).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+	reader_buf.try_drain_with_buf(0, &mut [], |_| {}).unwrap();
+}
+
+
+/// A non-blocking reader that fails with `WouldBlock` on every other `read` call
+struct NonBlockingReader<'a> {
+	data: &'a [u8],
+	ready: bool
+}
+impl Read for NonBlockingReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		self.ready = !self.ready;
+		match self.ready {
+			true => {
+				let len = buf.len().min(100);
+				self.data.read(&mut buf[..len])
+			},
+			false => Err(io::Error::from(io::ErrorKind::WouldBlock))
+		}
+	}
+}
+
+
+#[test]
+fn test_try_read_exact_would_block() {
+	let data = include_bytes!("predefined_0.macos");
+	let mut reader = NonBlockingReader{ data, ready: false };
+	
+	// Retry with the remaining buffer until the read completes
+	let (mut buf, mut pos, mut retries) = (vec![0; data.len()], 0, 0);
+	while let Err(e) = reader.try_read_exact(&mut buf[pos..], |read| pos += read) {
+		assert_eq!(e.kind(), io::ErrorKind::WouldBlock);
+		retries += 1;
+	}
+	assert_eq!(buf.as_slice(), data as &[u8]);
+	assert_eq!(retries, 3);
+}
+
+
+#[test]
+fn test_drain_buffered() {
+	let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+	let mut reader = BufReader::with_capacity(512, ChunkedReader{ data: &data, chunk: 100 });
+	
+	// Drain across multiple buffer refills and verify the position
+	reader.drain_buffered(9_000).unwrap();
+	let mut next = [0; 1];
+	reader.read_exact(&mut next).unwrap();
+	assert_eq!(next[0], data[9_000]);
+	
+	// Draining past the end must fail
+	let err = reader.drain_buffered(1_000).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+
+#[test]
+fn test_try_fill_with() {
+	let mut written = 0;
+	let mut buf = Vec::new();
+	buf.try_fill_with(5000, 0xaa, |len| written += len).unwrap();
+	assert_eq!(written, 5000);
+	assert!(buf.len() == 5000 && buf.iter().all(|b| *b == 0xaa));
+	
+	let mut buf = Vec::new();
+	buf.try_fill(3, |_| {}).unwrap();
+	assert_eq!(buf, [0, 0, 0]);
+}
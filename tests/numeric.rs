@@ -0,0 +1,261 @@
+use basic_tar::{
+	BasicTarError, Header,
+	raw::{ self, TypeFlag }
+};
+
+
+#[test]
+fn test_base256_size() {
+	let header = Header::builder("large.bin")
+		.mode(0o644).uid(0o765).gid(0o24)
+		.size(16 * 1024 * 1024 * 1024).mtime(0o13521657412)
+		.build();
+	assert_eq!(header.typeflag, TypeFlag::REGULAR);
+	
+	// Serialize the header and verify the base-256 encoding
+	let raw = header.serialize().unwrap();
+	assert_eq!(&raw[124..136], &[0x80, 0, 0, 0, 0, 0, 0, 0x04, 0, 0, 0, 0]);
+	assert_eq!(Header::parse(&raw).unwrap(), header);
+}
+
+
+#[test]
+fn test_base256_overflow() {
+	let header = Header::builder("large.bin").uid(u64::MAX).build();
+	assert_eq!(
+		header.serialize().unwrap_err(),
+		BasicTarError::FieldTooSmall{ needed: 9, available: 8 }
+	);
+}
+
+
+#[test]
+fn test_payload_len_checked() {
+	let header = Header::builder("small.bin").size(u32::MAX as u64).build();
+	assert_eq!(header.payload_len_checked(), Ok(u32::MAX as usize));
+	
+	let header = Header::builder("large.bin").size(u32::MAX as u64 + 1).build();
+	#[cfg(target_pointer_width = "32")]
+	assert_eq!(
+		header.payload_len_checked(),
+		Err(BasicTarError::Unsupported("Payload size exceeds addressable memory"))
+	);
+	#[cfg(target_pointer_width = "64")]
+	assert_eq!(header.payload_len_checked(), Ok(u32::MAX as usize + 1));
+}
+
+
+#[test]
+fn test_space_padded_octal() {
+	// Create a header with space-padded numeric fields
+	let mut raw = raw::header::raw();
+	raw[..18].copy_from_slice(b"predefined_0.plain");
+	raw[100..108].copy_from_slice(b"   644 \0");
+	raw[108..116].copy_from_slice(b"  0765\0 ");
+	raw[124..136].copy_from_slice(b"         11\0");
+	raw[156] = TypeFlag::REGULAR;
+	
+	let checksum = raw::checksum(&raw);
+	raw[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+	
+	let header = Header::parse(&raw).unwrap();
+	assert_eq!(header.mode, Some(0o644));
+	assert_eq!(header.uid, Some(0o765));
+	assert_eq!(header.size, 0o11);
+}
+
+
+#[test]
+fn test_max_octal_size() {
+	let header = Header::builder("large.bin").size(raw::MAX_OCTAL_SIZE).build();
+	assert_eq!(&header.serialize().unwrap()[124..136], b"77777777777\0");
+	
+	let header = Header::builder("large.bin").size(raw::MAX_OCTAL_SIZE + 1).build();
+	assert_eq!(header.serialize().unwrap()[124], 0x80);
+}
+
+
+#[test]
+fn test_unterminated_octal() {
+	// Create a header whose size field is completely filled with octal digits
+	let mut raw = raw::header::raw();
+	raw[..18].copy_from_slice(b"predefined_0.plain");
+	raw[124..136].copy_from_slice(b"000000000777");
+	raw[136..148].copy_from_slice(b"135216574120");
+	raw[156] = TypeFlag::REGULAR;
+	
+	let checksum = raw::checksum(&raw);
+	raw[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+	
+	let header = Header::parse(&raw).unwrap();
+	assert_eq!(header.size, 0o777);
+	assert_eq!(header.mtime, Some(0o135216574120));
+	assert_eq!(raw::header::Header::from(raw).size_octal(), Ok(0o777));
+}
+
+
+#[test]
+fn test_unterminated_octal_max() {
+	// Create a header whose size field is completely filled with the largest octal digit
+	let mut raw = raw::header::raw();
+	raw[..18].copy_from_slice(b"predefined_0.plain");
+	raw[124..136].copy_from_slice(b"777777777777");
+	raw[136..148].copy_from_slice(b"135216574120");
+	raw[156] = TypeFlag::REGULAR;
+	raw::rewrite_checksum(&mut raw);
+	
+	// The field is parsed completely without touching the adjacent modification time
+	let header = Header::parse(&raw).unwrap();
+	assert_eq!(header.size, 0o777777777777);
+	assert_eq!(header.mtime, Some(0o135216574120));
+	
+	// A non-octal digit within the unterminated field is rejected cleanly
+	raw[135] = b'8';
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Invalid octal number")));
+}
+
+
+#[test]
+fn test_plus_signed_octal() {
+	// Create a header whose numeric fields carry a leading plus sign
+	let mut raw = raw::header::raw();
+	raw[..18].copy_from_slice(b"predefined_0.plain");
+	raw[100..105].copy_from_slice(b"+644\0");
+	raw[108..114].copy_from_slice(b" +765\0");
+	raw[124..136].copy_from_slice(b"+0000000011\0");
+	raw[156] = TypeFlag::REGULAR;
+	raw::rewrite_checksum(&mut raw);
+	
+	let header = Header::parse(&raw).unwrap();
+	assert_eq!(header.mode, Some(0o644));
+	assert_eq!(header.uid, Some(0o765));
+	assert_eq!(header.size, 0o11);
+	
+	// A minus sign or a sign without digits is still rejected
+	raw[100..105].copy_from_slice(b"-644\0");
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Invalid octal number")));
+	
+	raw[100..105].copy_from_slice(b"+\0\0\0\0");
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Invalid octal number")));
+}
+
+
+#[test]
+fn test_record_len() {
+	assert_eq!(Header::builder("empty.bin").size(0).build().record_len(), Ok(512));
+	assert_eq!(Header::builder("small.bin").size(1).build().record_len(), Ok(1024));
+	assert_eq!(Header::builder("block.bin").size(512).build().record_len(), Ok(1024));
+	assert_eq!(Header::builder("large.bin").size(513).build().record_len(), Ok(1536));
+}
+
+
+#[test]
+fn test_record_len_with() {
+	let header = Header::builder("small.bin").size(1).build();
+	assert_eq!(header.record_len_with(512), header.record_len());
+	assert_eq!(header.record_len_with(1024), Ok(1536));
+	assert_eq!(header.record_len_with(4096), Ok(4608));
+	assert_eq!(Header::builder("empty.bin").size(0).build().record_len_with(4096), Ok(512));
+	
+	// A block length of zero is treated as one and overflows are reported as error
+	assert_eq!(header.record_len_with(0), Ok(513));
+	assert_eq!(
+		Header::builder("huge.bin").size(u64::MAX).build().record_len(),
+		Err(BasicTarError::Unsupported("Record length exceeds the 64-bit range"))
+	);
+}
+
+
+#[test]
+fn test_base256_mtime() {
+	// A modification time past the octal range is encoded and decoded as base-256 number
+	let header = Header::builder("future.bin").mtime(1 << 40).build();
+	let raw = header.serialize().unwrap();
+	assert_eq!(&raw[136..148], &[0x80, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0, 0]);
+	assert_eq!(Header::parse(&raw).unwrap().mtime, Some(1 << 40));
+	assert_eq!(raw::header::Header::from(raw).mtime_signed(), Ok(1 << 40));
+	
+	// A negative modification time is rejected by `parse` but decoded by `mtime_signed`
+	let mut raw = Header::builder("past.bin").build().serialize().unwrap();
+	raw[136..148].copy_from_slice(&[0xff; 12]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::Unsupported("Negative base-256 numbers are not supported"))
+	);
+	assert_eq!(raw::header::Header::from(raw).mtime_signed(), Ok(-1));
+	
+	raw[146..148].copy_from_slice(&[0xfe, 0x00]);
+	assert_eq!(raw::header::Header::from(raw).mtime_signed(), Ok(-512));
+	raw[137] = 0x00;
+	assert_eq!(
+		raw::header::Header::from(raw).mtime_signed(),
+		Err(BasicTarError::Unsupported("Base-256 number is too large"))
+	);
+}
+
+
+#[test]
+fn test_base256_size_too_large() {
+	// A base-256 size that would overflow the record length computations is rejected
+	let mut raw = Header::builder("huge.bin").build().serialize().unwrap();
+	raw[124] = 0x80;
+	raw[125..136].copy_from_slice(&[0xff; 11]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::Unsupported("Base-256 number is too large"))
+	);
+	
+	raw[125..128].copy_from_slice(&[0; 3]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Size is too large")));
+	assert_eq!(
+		raw::header::Header::from(raw).size_octal(),
+		Err(BasicTarError::InvalidData("Size is too large"))
+	);
+	
+	// The largest supported size is still accepted
+	raw[128..136].copy_from_slice(&raw::MAX_SIZE.to_be_bytes());
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw).unwrap().size, raw::MAX_SIZE);
+}
+
+
+#[test]
+fn test_base256_lead_byte() {
+	// The remaining bits of a positive lead byte are part of the number
+	let mut raw = Header::builder("large.bin").build().serialize().unwrap();
+	raw[124..136].copy_from_slice(&[0x81, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::Unsupported("Base-256 number is too large"))
+	);
+	
+	raw[124..136].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0]);
+	raw[136..148].copy_from_slice(&[0x81, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::Unsupported("Base-256 number is too large"))
+	);
+	raw[136..148].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02, 0]);
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw).unwrap().mtime, Some(512));
+}
+
+
+#[test]
+fn test_octal_only_fields() {
+	// Only the size, UID, GID and modification time fall back to base-256
+	let header = Header::builder("large.bin").mode(0o10000000).build();
+	assert_eq!(header.validate(), Err(BasicTarError::Unsupported("Mode is too large")));
+	assert_eq!(
+		header.serialize().unwrap_err(),
+		BasicTarError::FieldTooSmall{ needed: 9, available: 8 }
+	);
+}
@@ -0,0 +1,116 @@
+use basic_tar::{ BasicTarError, Header, raw::pax };
+
+
+#[test]
+fn test_parse() {
+	let pairs = pax::parse(include_bytes!("predefined_0.pax")).unwrap();
+	assert_eq!(pairs, vec![("mtime".to_string(), "1564958474.767716672".to_string())]);
+	
+	let pairs = pax::parse(include_bytes!("predefined_1.pax")).unwrap();
+	assert_eq!(pairs, vec![("mtime".to_string(), "1564957438.6623".to_string())]);
+}
+
+
+#[test]
+fn test_parse_delimiters() {
+	// A value may contain `=` and is only split at the first one
+	let pairs = pax::parse(b"29 SCHILY.xattr.user.a=b=c==\n").unwrap();
+	assert_eq!(pairs, vec![("SCHILY.xattr.user.a".to_string(), "b=c==".to_string())]);
+	
+	// A value may contain newlines and spans the entire declared length
+	let pairs = pax::parse(b"25 comment=line 1\nline 2\n12 path=abc\n").unwrap();
+	assert_eq!(pairs, vec![
+		("comment".to_string(), "line 1\nline 2".to_string()),
+		("path".to_string(), "abc".to_string())
+	]);
+	assert_eq!(pax::parse(&pax::serialize(&[("comment", "a=\nb")])).unwrap()[0].1, "a=\nb");
+}
+
+
+#[test]
+fn test_parse_bytes() {
+	// Only the keys must be UTF-8
+	let pairs = pax::parse_bytes(b"26 SCHILY.xattr.user.a=\xff\x00\n12 path=abc\n").unwrap();
+	assert_eq!(pairs, vec![
+		("SCHILY.xattr.user.a".to_string(), b"\xff\x00".to_vec()),
+		("path".to_string(), b"abc".to_vec())
+	]);
+	assert_eq!(
+		pax::parse(b"26 SCHILY.xattr.user.a=\xff\x00\n").unwrap_err(),
+		BasicTarError::InvalidData("Pax record is not UTF-8")
+	);
+	assert_eq!(
+		pax::parse_bytes(b"8 \xff=abc\n").unwrap_err(),
+		BasicTarError::InvalidData("Pax record is not UTF-8")
+	);
+}
+
+
+#[test]
+fn test_xattrs() {
+	let payload = pax::serialize(&[
+		("SCHILY.xattr.security.selinux", "system_u:object_r:user_home_t:s0"),
+		("mtime", "1564958474.767716672"),
+		("SCHILY.xattr.user.comment", "a=b")
+	]);
+	let xattrs = pax::xattrs(&pax::parse_bytes(&payload).unwrap());
+	assert_eq!(xattrs, vec![
+		("security.selinux".to_string(), b"system_u:object_r:user_home_t:s0".to_vec()),
+		("user.comment".to_string(), b"a=b".to_vec())
+	]);
+	
+	// Values are returned as raw bytes
+	let payload = b"29 SCHILY.xattr.user.bin=\x00\xff\n\n";
+	let xattrs = pax::xattrs(&pax::parse_bytes(payload).unwrap());
+	assert_eq!(xattrs, vec![("user.bin".to_string(), b"\x00\xff\n".to_vec())]);
+}
+
+
+#[test]
+fn test_parse_invalid() {
+	let invalid_length = BasicTarError::InvalidData("Invalid pax record length");
+	assert_eq!(pax::parse(b"x mtime=1\n").unwrap_err(), invalid_length);
+	assert_eq!(pax::parse(b"99 mtime=1\n").unwrap_err(), invalid_length);
+	assert_eq!(pax::parse(b"10mtime=1\n").unwrap_err(), invalid_length);
+	assert_eq!(
+		pax::parse(b"10 mtime=12\n").unwrap_err(),
+		BasicTarError::InvalidData("Pax record is not newline-terminated")
+	);
+	assert_eq!(
+		pax::parse(b"10 mtime1\n").unwrap_err(),
+		BasicTarError::InvalidData("Pax record has no key")
+	);
+}
+
+
+#[test]
+fn test_serialize() {
+	// Compare against bsdtar's encoding
+	let serialized = pax::serialize(&[("mtime", "1564958474.767716672")]);
+	assert_eq!(serialized.as_slice(), include_bytes!("predefined_0.pax") as &[u8]);
+	
+	// Test the power-of-ten boundaries of the length field
+	for value_len in 0..1100 {
+		let value = "v".repeat(value_len);
+		let pairs = [("key", value.as_str()), ("path", "predefined_0.plain")];
+		
+		let serialized = pax::serialize(&pairs);
+		let parsed = pax::parse(&serialized).unwrap();
+		let parsed: Vec<_> = parsed.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+		assert_eq!(parsed, pairs);
+	}
+}
+
+
+#[test]
+fn test_effective_size() {
+	let header = Header::builder("predefined_0.plain").size(0).build();
+	assert_eq!(header.effective_size(&[]), Ok(0));
+	
+	let serialized = pax::serialize(&[("size", "8589934592"), ("path", "large.bin")]);
+	let pairs = pax::parse(&serialized).unwrap();
+	assert_eq!(header.effective_size(&pairs), Ok(8589934592));
+	
+	let pairs = pax::parse(&pax::serialize(&[("size", "-1")])).unwrap();
+	assert_eq!(header.effective_size(&pairs), Err(BasicTarError::InvalidData("Invalid pax size")));
+}
@@ -1,10 +1,13 @@
+#![cfg(feature = "std")]
+
 mod tar_record;
 
 use basic_tar::{
-	BasicTarError, Header, WriteExt,
-	raw::{ TypeFlag, BLOCK_LEN }
+	ArchiveReader, ArchiveWriter, BasicTarError, Format, Header, Mode, PayloadReader,
+	RecordType, WriteExt,
+	raw::{ self, pax, TypeFlag, BLOCK_LEN }
 };
-use std::io::Cursor;
+use std::io::{ Cursor, Read };
 
 
 /// A test vector to test archive (de-)serialization
@@ -50,11 +53,44 @@ impl TestVector {
 		// Compare data
 		let archive = stream.into_inner();
 		assert_eq!(archive.len(), self.archive.len());
-		assert_eq!(archive.as_slice(), self.archive);
+		assert_eq_ignoring_checksums(archive.as_slice(), self.archive);
 	}
 }
 
 
+/// Asserts that the archives `left` and `right` are equal except for the layout of the header
+/// checksums (older archives may use a different but valid checksum layout)
+fn assert_eq_ignoring_checksums(left: &[u8], right: &[u8]) {
+	assert_eq!(left.len(), right.len());
+	let mut offset = 0;
+	while offset < left.len() {
+		// Compare the headers and skip the payloads
+		let mut header_raw = raw::header::raw();
+		header_raw.copy_from_slice(&left[offset..offset + BLOCK_LEN]);
+		let this = raw::header::Header::from(header_raw);
+		header_raw.copy_from_slice(&right[offset..offset + BLOCK_LEN]);
+		let other = raw::header::Header::from(header_raw);
+		assert!(this.eq_ignoring_checksum(&other), "Invalid header at offset {}", offset);
+		
+		let payload_len = match raw::is_zero_block(&header_raw) {
+			true => 0,
+			false => Header::parse(&header_raw).unwrap().record_len().unwrap() as usize - BLOCK_LEN
+		};
+		let (start, end) = (offset + BLOCK_LEN, offset + BLOCK_LEN + payload_len);
+		assert_eq!(&left[start..end], &right[start..end], "Invalid payload at {}", offset);
+		offset = end;
+	}
+}
+
+
+/// Reads the next header from `stream`
+fn read_header(mut stream: impl Read) -> Header {
+	let mut header_raw = raw::header::raw();
+	stream.read_exact(&mut header_raw).unwrap();
+	Header::parse(&header_raw).unwrap()
+}
+
+
 #[test]
 fn test_read() {
 	TestVector {
@@ -65,7 +101,9 @@ fn test_read() {
 					path: "predefined_0.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o11, mtime: Some(0o13521071532),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: None, gname: None, magic: None, version: None,
+					..Default::default()
 				},
 				include_bytes!("predefined_0.plain")
 			),
@@ -75,7 +113,9 @@ fn test_read() {
 					path: "predefined_1.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o12, mtime: Some(0o13521071556),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: None, gname: None, magic: None, version: None,
+					..Default::default()
 				},
 				include_bytes!("predefined_1.plain")
 			)
@@ -90,7 +130,10 @@ fn test_read() {
 					path: "._predefined_0.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o600, mtime: Some(0o13521657412),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_0.macos")
 			),
@@ -99,7 +142,10 @@ fn test_read() {
 					path: "PaxHeader/predefined_0.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o36, mtime: Some(0o13521657412),
-					typeflag: TypeFlag::PAX_SINGLE, linkname: None
+					typeflag: TypeFlag::PAX_SINGLE, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_0.pax")
 			),
@@ -108,7 +154,10 @@ fn test_read() {
 					path: "predefined_0.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o11, mtime: Some(0o13521657412),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_0.plain")
 			),
@@ -118,7 +167,10 @@ fn test_read() {
 					path: "._predefined_1.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o600, mtime: Some(0o13521655376),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_1.macos")
 			),
@@ -127,7 +179,10 @@ fn test_read() {
 					path: "PaxHeader/predefined_1.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o31, mtime: Some(0o13521655376),
-					typeflag: TypeFlag::PAX_SINGLE, linkname: None
+					typeflag: TypeFlag::PAX_SINGLE, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_1.pax")
 			),
@@ -136,10 +191,699 @@ fn test_read() {
 					path: "predefined_1.plain".into(),
 					mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
 					size: 0o12, mtime: Some(0o13521655376),
-					typeflag: TypeFlag::REGULAR, linkname: None
+					typeflag: TypeFlag::REGULAR, linkname: None,
+					uname: Some("keziah".into()), gname: Some("staff".into()),
+					magic: Some(*b"ustar\0"), version: Some(*b"00"),
+					..Default::default()
 				},
 				include_bytes!("predefined_1.plain")
 			)
 		]
 	}.test_read();
-}
\ No newline at end of file
+}
+
+
+#[test]
+fn test_record_type() {
+	let header = Header { typeflag: TypeFlag::PAX_SINGLE, ..Default::default() };
+	assert_eq!(header.record_type(), RecordType::PaxSingle);
+	
+	for typeflag in 0..=u8::MAX {
+		assert_eq!(u8::from(RecordType::from(typeflag)), typeflag);
+	}
+	assert_eq!(RecordType::from(b'7'), RecordType::Other(b'7'));
+}
+
+
+#[test]
+fn test_record_type_display() {
+	assert_eq!(RecordType::Regular.to_string(), "regular file");
+	assert_eq!(RecordType::Directory.to_string(), "directory");
+	assert_eq!(RecordType::SymLink.to_string(), "symbolic link");
+	assert_eq!(RecordType::from(TypeFlag::GNU_LONGNAME).to_string(), "GNU long name");
+	assert_eq!(RecordType::from(b'7').to_string(), "unknown (0x37)");
+}
+
+
+#[test]
+fn test_typeflag_all() {
+	let all = TypeFlag::all();
+	assert!(all.contains(&(TypeFlag::REGULAR, "regular file")));
+	assert!(all.contains(&(TypeFlag::GNU_MULTIVOL, "GNU multi-volume continuation")));
+	
+	// The names match the record type descriptions and every flag is listed once
+	for (index, (flag, name)) in all.iter().enumerate() {
+		assert_eq!(RecordType::from(*flag).to_string(), *name);
+		assert!(all[index + 1..].iter().all(|(other, _)| other != flag));
+	}
+}
+
+
+#[test]
+fn test_legacy_typeflag() {
+	// Serialize a regular file and a directory with the legacy NUL type flag
+	let file = Header::builder("predefined_0.plain").typeflag(TypeFlag::REGULAR_LEGACY).build();
+	let dir = Header::builder("predefined/").typeflag(TypeFlag::REGULAR_LEGACY).build();
+	
+	let mut file = Header::parse(&file.serialize().unwrap()).unwrap();
+	assert_eq!(file.typeflag, TypeFlag::REGULAR_LEGACY);
+	assert!(file.is_regular_file());
+	file.normalize_typeflag();
+	assert_eq!(file.typeflag, TypeFlag::REGULAR);
+	
+	let mut dir = Header::parse(&dir.serialize().unwrap()).unwrap();
+	assert!(dir.is_directory());
+	dir.normalize_typeflag();
+	assert_eq!(dir.typeflag, TypeFlag::DIRECTORY);
+}
+
+
+#[test]
+fn test_predicates() {
+	let header = Header::builder("predefined_0.plain").build();
+	assert!(header.is_regular_file() && !header.is_directory());
+	
+	let header = Header::builder("predefined_0.plain").typeflag(0).build();
+	assert!(header.is_regular_file() && !header.is_directory());
+	
+	let header = Header::builder("predefined/").typeflag(0).build();
+	assert!(!header.is_regular_file() && header.is_directory());
+	
+	let header = Header::builder("predefined").typeflag(TypeFlag::DIRECTORY).build();
+	assert!(!header.is_regular_file() && header.is_directory());
+	
+	let header = Header::builder("link").typeflag(TypeFlag::SYMLINK).build();
+	assert!(header.is_symlink() && !header.is_hardlink() && !header.is_regular_file());
+	
+	let header = Header::builder("link").typeflag(TypeFlag::HARDLINK).build();
+	assert!(header.is_hardlink() && !header.is_symlink());
+	
+	let header = Header::builder("tty").typeflag(TypeFlag::CHAR_DEV).build();
+	assert!(header.is_device());
+	let header = Header::builder("sda").typeflag(TypeFlag::BLOCK_DEV).build();
+	assert!(header.is_device());
+}
+
+
+#[test]
+fn test_read_record() {
+	let mut stream = Cursor::new(include_bytes!("predefined_nul.tar") as &[u8]);
+	
+	let (header, payload) = basic_tar::read_record(&mut stream).unwrap().unwrap();
+	assert_eq!(header.path, "predefined_0.plain");
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_0.plain"));
+	
+	let (header, payload) = basic_tar::read_record(&mut stream).unwrap().unwrap();
+	assert_eq!(header.path, "predefined_1.plain");
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_1.plain"));
+	
+	assert_eq!(basic_tar::read_record(&mut stream), Ok(None));
+	assert_eq!(basic_tar::read_record(&mut stream), Ok(None));
+	assert_eq!(
+		basic_tar::read_record(&mut stream),
+		Err(BasicTarError::Io(std::io::ErrorKind::UnexpectedEof))
+	);
+}
+
+
+#[test]
+fn test_truncated() {
+	let archive = include_bytes!("predefined_nul.tar");
+	
+	// Truncate the archive within the header and within the payload and padding
+	assert_eq!(
+		basic_tar::read_record(&archive[..100]),
+		Err(BasicTarError::Io(std::io::ErrorKind::UnexpectedEof))
+	);
+	assert_eq!(
+		basic_tar::read_record(&archive[..BLOCK_LEN + 5]),
+		Err(BasicTarError::Truncated{ expected: 512, got: 5 })
+	);
+	assert_eq!(
+		basic_tar::read_record(&archive[..BLOCK_LEN + 100]),
+		Err(BasicTarError::Truncated{ expected: 512, got: 100 })
+	);
+	
+	let mut stream = &archive[..BLOCK_LEN + 100];
+	let header = read_header(&mut stream);
+	assert_eq!(
+		basic_tar::skip_record(&mut stream, &header),
+		Err(BasicTarError::Truncated{ expected: 512, got: 100 })
+	);
+}
+
+
+#[test]
+fn test_io_error() {
+	let error = std::io::Error::new(std::io::ErrorKind::TimedOut, "Timeout");
+	assert_eq!(BasicTarError::from(error), BasicTarError::Io(std::io::ErrorKind::TimedOut));
+}
+
+
+#[test]
+fn test_parse_from_reader() {
+	let archive = include_bytes!("predefined_nul.tar");
+	let mut stream = &archive[..];
+	
+	let header = Header::parse_from_reader(&mut stream).unwrap();
+	assert_eq!(header.path, "predefined_0.plain");
+	assert_eq!(stream.len(), archive.len() - BLOCK_LEN);
+	
+	// An all-zero block and a truncated block are reported as errors
+	let eof = &archive[archive.len() - BLOCK_LEN..];
+	assert_eq!(Header::parse_from_reader(eof), Err(BasicTarError::EmptyHeader));
+	assert_eq!(
+		Header::parse_from_reader(&archive[..100]),
+		Err(BasicTarError::Io(std::io::ErrorKind::UnexpectedEof))
+	);
+}
+
+
+#[test]
+fn test_write_to() {
+	let header = Header::builder("predefined_0.plain").mode(0o644).size(9).build();
+	let mut written = Vec::new();
+	header.write_to(&mut written).unwrap();
+	assert_eq!(written.as_slice(), &header.serialize().unwrap()[..]);
+	
+	// Serialization and I/O errors are unified
+	let invalid = Header::builder("x".repeat(300)).build();
+	assert!(invalid.write_to(&mut written).is_err());
+	assert_eq!(written.len(), BLOCK_LEN);
+	assert_eq!(
+		header.write_to(&mut [0; 100][..]),
+		Err(BasicTarError::Io(std::io::ErrorKind::WriteZero))
+	);
+}
+
+
+#[test]
+fn test_skip_record() {
+	let mut stream = Cursor::new(include_bytes!("predefined_nul.tar") as &[u8]);
+	
+	let (header, _) = basic_tar::read_record(&mut stream).unwrap().unwrap();
+	assert_eq!(header.path, "predefined_0.plain");
+	
+	// Skip the second record
+	let header = read_header(&mut stream);
+	basic_tar::skip_record(&mut stream, &header).unwrap();
+	
+	assert_eq!(stream.position(), BLOCK_LEN as u64 * 4);
+	assert_eq!(basic_tar::read_record(&mut stream), Ok(None));
+}
+
+
+#[test]
+fn test_pad_to_block() {
+	let mut buf = include_bytes!("predefined_0.plain").to_vec();
+	assert_eq!(buf.len(), 9);
+	assert_eq!(basic_tar::pad_to_block(&mut buf), 503);
+	assert_eq!(buf.len(), 512);
+	assert!(buf[9..].iter().all(|b| *b == 0));
+	
+	// An aligned buffer is left untouched
+	assert_eq!(basic_tar::pad_to_block(&mut buf), 0);
+	assert_eq!(buf.len(), 512);
+}
+
+
+#[test]
+fn test_write_record() {
+	// Read the records and write them again
+	let archive = include_bytes!("predefined_nul.tar");
+	let (mut reader, mut writer) = (Cursor::new(archive as &[u8]), Cursor::new(Vec::new()));
+	while let Some((header, payload)) = basic_tar::read_record(&mut reader).unwrap() {
+		basic_tar::write_record(header, &payload, &mut writer).unwrap();
+	}
+	
+	// Compare the records (excluding the EOF blocks)
+	let mut written = writer.into_inner();
+	assert_eq_ignoring_checksums(written.as_slice(), &archive[..archive.len() - BLOCK_LEN * 2]);
+	
+	// Write and check the EOF blocks
+	basic_tar::write_eof(&mut written).unwrap();
+	assert!(raw::is_zero_block(&raw::EOF_BLOCK));
+	assert_eq_ignoring_checksums(written.as_slice(), archive);
+}
+
+
+#[test]
+fn test_write_trailer() {
+	for blocks in [0, 2, 20].iter() {
+		let mut written = Vec::new();
+		basic_tar::write_trailer(&mut written, *blocks).unwrap();
+		assert_eq!(written.len(), blocks * BLOCK_LEN);
+		assert!(written.iter().all(|b| *b == 0));
+	}
+}
+
+
+#[test]
+fn test_align_archive_len() {
+	assert_eq!(basic_tar::align_archive_len(0, 20), 0);
+	assert_eq!(basic_tar::align_archive_len(1024, 20), 10240);
+	assert_eq!(basic_tar::align_archive_len(10240, 20), 10240);
+	assert_eq!(basic_tar::align_archive_len(10752, 20), 20480);
+	assert_eq!(basic_tar::align_archive_len(1536, 1), 1536);
+	assert_eq!(basic_tar::align_archive_len(1536, 0), 1536);
+}
+
+
+#[test]
+fn test_archive_writer_finish_aligned() {
+	// A small archive is padded to the GNU tar default of 20 blocks
+	let mut writer = ArchiveWriter::new(Vec::new()).mtime(0);
+	writer.append_data("predefined_0.plain", include_bytes!("predefined_0.plain")).unwrap();
+	let archive = writer.finish_aligned(20).unwrap();
+	assert_eq!(archive.len(), 10240);
+	assert!(archive[BLOCK_LEN * 2..].iter().all(|b| *b == 0));
+	
+	// The end of archive indicator is always written, even if the records are already aligned
+	let mut writer = ArchiveWriter::new(Vec::new()).mtime(0);
+	writer.append_data("predefined_0.plain", include_bytes!("predefined_0.plain")).unwrap();
+	assert_eq!(writer.finish_aligned(2).unwrap().len(), 2048);
+}
+
+
+#[test]
+fn test_device_records() {
+	let archive = include_bytes!("predefined_dev.tar");
+	let expected = [
+		Header::builder("null").typeflag(TypeFlag::CHAR_DEV).mode(0o666).uid(0).gid(0)
+			.mtime(0o13521657412).device(1, 3).build(),
+		Header::builder("sda").typeflag(TypeFlag::BLOCK_DEV).mode(0o660).uid(0).gid(0)
+			.mtime(0o13521657412).device(8, 0).build()
+	];
+	for ((block, expected), gname) in archive.chunks(BLOCK_LEN).zip(expected.iter())
+		.zip(["root", "disk"].iter())
+	{
+		let mut header_raw = raw::header::raw();
+		header_raw.copy_from_slice(block);
+		
+		// Parse the header and verify the device numbers
+		let header = Header::parse(&header_raw).unwrap();
+		assert_eq!(header, Header {
+			uname: Some("root".into()), gname: Some(gname.to_string()),
+			magic: Some(*b"ustar\0"), version: Some(*b"00"),
+			..expected.clone()
+		});
+		assert!(header.is_device());
+		
+		// Serialize the header again and compare it with the test vector
+		assert_eq!(header.serialize().unwrap()[..], header_raw[..]);
+	}
+	
+	// The device records are followed by the end of archive indicator
+	assert!(archive[BLOCK_LEN * 2..].iter().all(|b| *b == 0));
+	
+	// The (zero) device numbers of non-device records are ignored
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&include_bytes!("predefined_bsd.tar")[..BLOCK_LEN]);
+	assert_eq!(&header_raw[329..345], b"000000 \x00000000 \0");
+	let header = Header::parse(&header_raw).unwrap();
+	assert_eq!((header.devmajor, header.devminor), (None, None));
+}
+
+
+#[test]
+fn test_archive_reader() {
+	// Append some trailing garbage after the end of archive indicator
+	let mut archive = include_bytes!("predefined_bsd.tar").to_vec();
+	archive.extend_from_slice(b"Trailing garbage");
+	
+	let paths: Vec<String> = ArchiveReader::new(Cursor::new(archive.as_slice()))
+		.pax_overrides(false)
+		.map(|record| record.unwrap().0.path)
+		.collect();
+	assert_eq!(paths, [
+		"._predefined_0.plain", "PaxHeader/predefined_0.plain", "predefined_0.plain",
+		"._predefined_1.plain", "PaxHeader/predefined_1.plain", "predefined_1.plain"
+	]);
+	
+	// Pax records are applied transparently by default
+	let records: Vec<(String, Option<u64>)> = ArchiveReader::new(Cursor::new(archive.as_slice()))
+		.map(|record| record.unwrap().0)
+		.map(|header| (header.path, header.mtime))
+		.collect();
+	assert_eq!(records, [
+		("._predefined_0.plain".to_string(), Some(1564958474)),
+		("predefined_0.plain".to_string(), Some(1564958474)),
+		("._predefined_1.plain".to_string(), Some(1564957438)),
+		("predefined_1.plain".to_string(), Some(1564957438))
+	]);
+}
+
+
+#[test]
+fn test_archive_reader_pax() {
+	let pax_record = |typeflag, pairs: &[(&str, &str)]| {
+		let payload = pax::serialize(pairs);
+		let header = Header::builder("PaxHeader").typeflag(typeflag).size(payload.len() as u64);
+		(header.build(), payload)
+	};
+	
+	// Create an archive with a global record and a single record that overrides the size
+	let mut writer = ArchiveWriter::new(Vec::new());
+	let (header, payload) = pax_record(TypeFlag::PAX_GLOBAL, &[("mtime", "1564958474.767716672")]);
+	writer.append(header, &payload).unwrap();
+	let pairs = [("path", "long.plain"), ("size", "9")];
+	let (header, payload) = pax_record(TypeFlag::PAX_SINGLE, &pairs);
+	writer.append(header, &payload).unwrap();
+	writer.append(Header::builder("short.plain").build(), b"Testolope").unwrap();
+	writer.append(Header::builder("other.plain").mtime(7).build(), &[]).unwrap();
+	let archive = writer.finish().unwrap();
+	
+	// Read the archive
+	let records: Vec<_> = ArchiveReader::new(Cursor::new(archive.as_slice()))
+		.map(|record| record.unwrap())
+		.map(|(header, payload)| (header.path, header.mtime, payload))
+		.collect();
+	assert_eq!(records, [
+		("long.plain".to_string(), Some(1564958474), b"Testolope".to_vec()),
+		("other.plain".to_string(), Some(1564958474), Vec::new())
+	]);
+	assert_eq!(ArchiveReader::new(Cursor::new(archive.as_slice())).pax_overrides(false).count(), 4);
+}
+
+
+#[test]
+fn test_archive_reader_pax_binary() {
+	// Create a pax record with a binary xattr value next to an applied key
+	let mut payload = b"26 SCHILY.xattr.user.a=\xff\x00\n".to_vec();
+	payload.extend_from_slice(b"19 path=long.plain\n");
+	let mut writer = ArchiveWriter::new(Vec::new());
+	let header = Header::builder("PaxHeader").typeflag(TypeFlag::PAX_SINGLE);
+	writer.append(header.size(payload.len() as u64).build(), &payload).unwrap();
+	writer.append(Header::builder("short.plain").size(9).build(), b"Testolope").unwrap();
+	let archive = writer.finish().unwrap();
+	
+	// Only the applied keys must be UTF-8
+	let mut records = ArchiveReader::new(Cursor::new(archive.as_slice()));
+	assert_eq!(records.next().unwrap().unwrap().0.path, "long.plain");
+	assert!(records.next().is_none());
+	
+	// A binary value of an applied key is still rejected
+	let mut writer = ArchiveWriter::new(Vec::new());
+	let header = Header::builder("PaxHeader").typeflag(TypeFlag::PAX_SINGLE);
+	writer.append(header.size(10).build(), b"10 path=\xff\n").unwrap();
+	writer.append(Header::builder("short.plain").size(9).build(), b"Testolope").unwrap();
+	let archive = writer.finish().unwrap();
+	assert_eq!(
+		ArchiveReader::new(Cursor::new(archive.as_slice())).next(),
+		Some(Err(BasicTarError::InvalidData("Pax record is not UTF-8")))
+	);
+}
+
+
+#[test]
+fn test_archive_reader_filter_paths() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	
+	// Skip the macOS resource forks
+	let mut skipped = Vec::new();
+	let records: Vec<_> = ArchiveReader::new(Cursor::new(archive as &[u8]))
+		.filter_paths(|path| match path.starts_with("._") {
+			true => {
+				skipped.push(path.to_string());
+				false
+			},
+			false => true
+		})
+		.collect::<Result<_, _>>().unwrap();
+	
+	assert_eq!(skipped, ["._predefined_0.plain", "._predefined_1.plain"]);
+	assert_eq!(records.len(), 2);
+	assert_eq!(records[0].0.path, "predefined_0.plain");
+	assert_eq!(records[0].1.as_slice(), include_bytes!("predefined_0.plain"));
+	assert_eq!(records[1].0.path, "predefined_1.plain");
+	assert_eq!(records[1].1.as_slice(), include_bytes!("predefined_1.plain"));
+}
+
+
+#[test]
+fn test_archive_reader_total_len() {
+	let archive = include_bytes!("predefined_nul.tar");
+	
+	// A valid archive is read completely
+	let reader = ArchiveReader::new(Cursor::new(archive as &[u8]))
+		.with_total_len(archive.len() as u64);
+	assert!(reader.collect::<Result<Vec<_>, _>>().is_ok());
+	
+	// A corrupt size field is rejected before reading the payload
+	let mut corrupted = archive.to_vec();
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&corrupted[..BLOCK_LEN]);
+	header_raw[124..136].copy_from_slice(b"77777777777\0");
+	raw::rewrite_checksum(&mut header_raw);
+	corrupted[..BLOCK_LEN].copy_from_slice(&header_raw);
+	
+	let mut reader = ArchiveReader::new(Cursor::new(corrupted.as_slice()))
+		.with_total_len(corrupted.len() as u64);
+	assert_eq!(
+		reader.next(),
+		Some(Err(BasicTarError::InvalidData("Record size exceeds the archive length")))
+	);
+}
+
+
+#[test]
+fn test_parse_archive() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	let records = basic_tar::parse_archive(archive).unwrap();
+	assert_eq!(records.len(), 6);
+	assert_eq!(records[2].0.path, "predefined_0.plain");
+	assert_eq!(records[2].1.as_slice(), include_bytes!("predefined_0.plain"));
+	
+	// Truncate the archive within the last payload and within a block
+	assert_eq!(
+		basic_tar::parse_archive(&archive[..BLOCK_LEN * 11]),
+		Err(BasicTarError::Truncated{ expected: 512, got: 0 })
+	);
+	assert_eq!(
+		basic_tar::parse_archive(&archive[..BLOCK_LEN * 11 + 1]),
+		Err(BasicTarError::InvalidData("Archive length is not a multiple of the block size"))
+	);
+}
+
+
+#[test]
+fn test_space_terminator() {
+	// Create an archive and replace the end of archive indicator with space-filled blocks
+	let mut writer = ArchiveWriter::new(Vec::new());
+	writer.append_data("predefined_0.plain", include_bytes!("predefined_0.plain")).unwrap();
+	let mut archive = writer.finish().unwrap();
+	let eof = archive.len() - BLOCK_LEN * 2;
+	archive[eof..].iter_mut().for_each(|b| *b = b' ');
+	
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&archive[eof..eof + BLOCK_LEN]);
+	assert!(raw::is_empty_block(&header_raw) && !raw::is_zero_block(&header_raw));
+	assert_eq!(Header::parse(&header_raw), Err(BasicTarError::EmptyHeader));
+	
+	let records = basic_tar::parse_archive(&archive).unwrap();
+	assert_eq!(records.len(), 1);
+	assert_eq!(records[0].1.as_slice(), include_bytes!("predefined_0.plain"));
+	assert_eq!(basic_tar::verify_archive(archive.as_slice()), Ok(1));
+	
+	let reader = ArchiveReader::new(Cursor::new(archive.as_slice()));
+	assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap().len(), 1);
+}
+
+
+#[test]
+fn test_verify_archive() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	assert_eq!(basic_tar::verify_archive(&archive[..]), Ok(6));
+	
+	// Truncate the archive within the last payload and corrupt a checksum
+	assert_eq!(
+		basic_tar::verify_archive(&archive[..BLOCK_LEN * 11]),
+		Err(BasicTarError::Truncated{ expected: 512, got: 0 })
+	);
+	let mut corrupted = archive.to_vec();
+	corrupted[0] ^= 0x01;
+	assert_eq!(
+		basic_tar::verify_archive(corrupted.as_slice()),
+		Err(BasicTarError::InvalidData("Invalid header checksum"))
+	);
+}
+
+
+#[test]
+fn test_index_archive() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	let index = basic_tar::index_archive(&archive[..]).unwrap();
+	let records = basic_tar::parse_archive(archive).unwrap();
+	assert_eq!(index.len(), records.len());
+	
+	// The offsets must point to the header and the payload of each record
+	for (entry, (header, payload)) in index.iter().zip(records.iter()) {
+		assert_eq!(&entry.header, header);
+		let header_offset = entry.header_offset as usize;
+		assert_eq!(Header::parse_slice(&archive[header_offset..][..BLOCK_LEN]).unwrap(), *header);
+		let payload_offset = entry.payload_offset as usize;
+		assert_eq!(&archive[payload_offset..][..payload.len()], payload.as_slice());
+	}
+}
+
+
+#[test]
+fn test_read_record_at() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	let mut stream = Cursor::new(archive as &[u8]);
+	let index = basic_tar::index_archive(&mut stream).unwrap();
+	let records = basic_tar::parse_archive(archive).unwrap();
+	
+	// Read the records in reverse order
+	for (entry, record) in index.iter().zip(records.iter()).rev() {
+		assert_eq!(&basic_tar::read_record_at(&mut stream, entry.header_offset).unwrap(), record);
+	}
+	assert_eq!(
+		basic_tar::read_record_at(&mut stream, archive.len() as u64 - BLOCK_LEN as u64),
+		Err(BasicTarError::EmptyHeader)
+	);
+}
+
+
+#[test]
+fn test_archive_writer() {
+	let archive = include_bytes!("predefined_nul.tar");
+	
+	// Copy the archive
+	let mut writer = ArchiveWriter::new(Vec::new());
+	for record in ArchiveReader::new(Cursor::new(archive as &[u8])) {
+		let (header, payload) = record.unwrap();
+		writer.append(header, &payload).unwrap();
+	}
+	assert_eq!(writer.written(), (archive.len() - BLOCK_LEN * 2) as u64);
+	assert_eq_ignoring_checksums(writer.finish().unwrap().as_slice(), archive);
+}
+
+
+#[test]
+fn test_legacy_checksum_layout() {
+	// Older archives store the checksum as seven octal digits followed by a NUL, which still
+	// verifies but is re-serialized in the canonical layout
+	let archive = include_bytes!("predefined_nul.tar");
+	let mut header_raw = raw::header::raw();
+	header_raw.copy_from_slice(&archive[..BLOCK_LEN]);
+	assert_eq!(&header_raw[148..156], b"0010155\0");
+	
+	let header = Header::parse(&header_raw).unwrap();
+	let serialized = header.serialize().unwrap();
+	assert_eq!(&serialized[148..156], b"010155\0 ");
+	assert!(raw::header::Header::from(serialized).eq_ignoring_checksum(&header_raw.into()));
+}
+
+
+#[test]
+fn test_archive_writer_append_data() {
+	// Write a record with a fixed modification time
+	let mut writer = ArchiveWriter::new(Vec::new()).mtime(0o13521657412);
+	writer.append_data("predefined_0.plain", include_bytes!("predefined_0.plain")).unwrap();
+	let archive = writer.finish().unwrap();
+	
+	let records = basic_tar::parse_archive(&archive).unwrap();
+	let expected = Header::builder("predefined_0.plain").mode(0o644).uid(0).gid(0)
+		.size(9).mtime(0o13521657412);
+	assert_eq!(records, vec![(expected.build(), include_bytes!("predefined_0.plain").to_vec())]);
+	
+	// Use the current time by default
+	let mut writer = ArchiveWriter::new(Vec::new());
+	writer.append_data("predefined_0.plain", b"").unwrap();
+	let header = &basic_tar::parse_archive(&writer.finish().unwrap()).unwrap()[0].0;
+	assert!(header.mtime.unwrap() > 0o13521657412);
+}
+
+
+#[test]
+fn test_payload_reader() {
+	let mut stream = Cursor::new(include_bytes!("predefined_nul.tar") as &[u8]);
+	
+	// Stream the first payload
+	let header = read_header(&mut stream);
+	let mut payload = Vec::new();
+	let mut reader = PayloadReader::new(&mut stream, &header);
+	std::io::copy(&mut reader, &mut payload).unwrap();
+	assert_eq!(reader.remaining(), 0);
+	reader.finish().unwrap();
+	assert_eq!(payload.as_slice(), include_bytes!("predefined_0.plain"));
+	
+	// Drop the second payload reader without reading
+	let header = read_header(&mut stream);
+	drop(PayloadReader::new(&mut stream, &header));
+	assert_eq!(stream.position(), BLOCK_LEN as u64 * 4);
+}
+
+
+#[test]
+fn test_format() {
+	let formats = |archive: &'static [u8]| -> Vec<Format> {
+		ArchiveReader::new(Cursor::new(archive)).pax_overrides(false)
+			.map(|record| record.unwrap().0.format())
+			.collect()
+	};
+	assert_eq!(formats(include_bytes!("predefined_nul.tar")), [Format::Classic, Format::Classic]);
+	assert_eq!(formats(include_bytes!("predefined_gnu.tar")), [Format::Gnu]);
+	assert_eq!(
+		formats(include_bytes!("predefined_bsd.tar")),
+		[Format::Ustar, Format::Pax, Format::Ustar, Format::Ustar, Format::Pax, Format::Ustar]
+	);
+}
+
+
+#[test]
+fn test_mode_bits() {
+	let mode = Header::builder("setuid").mode(0o4755).build().mode_bits().unwrap();
+	assert!(mode.is_setuid() && !mode.is_setgid() && !mode.is_sticky());
+	assert_eq!(mode.permissions(), 0o755);
+	assert_eq!(mode.to_string(), "rwxr-xr-x");
+	
+	let mode = Mode::from(0o3640);
+	assert!(!mode.is_setuid() && mode.is_setgid() && mode.is_sticky());
+	assert_eq!(mode.to_string(), "rw-r-----");
+	
+	assert_eq!(Header::builder("none").build().mode_bits(), None);
+}
+
+
+#[test]
+fn test_effective_mode() {
+	assert_eq!(Header::builder("file").mode(0o600).build().effective_mode(), 0o600);
+	assert_eq!(Header::builder("file").build().effective_mode(), 0o644);
+	
+	let header = Header::builder("dir").typeflag(TypeFlag::DIRECTORY).build();
+	assert_eq!(header.effective_mode(), 0o755);
+	let header = Header::builder("dir/").typeflag(TypeFlag::REGULAR_LEGACY).build();
+	assert_eq!(header.effective_mode(), 0o755);
+}
+
+
+#[test]
+fn test_link_target() {
+	let header = Header::builder("link").typeflag(TypeFlag::SYMLINK).linkname("target".into())
+		.build();
+	assert_eq!(header.link_target(), Some("target"));
+	let header = Header::builder("link").typeflag(TypeFlag::HARDLINK).linkname("target".into())
+		.build();
+	assert_eq!(header.link_target(), Some("target"));
+	
+	// A stale link name on a regular file is ignored
+	let header = Header::builder("file").linkname("stale".into()).build();
+	assert_eq!(header.linkname.as_deref(), Some("stale"));
+	assert_eq!(header.link_target(), None);
+}
+
+
+#[test]
+fn test_reset() {
+	let mut header = Header::builder("link").typeflag(TypeFlag::SYMLINK).linkname("target".into())
+		.mode(0o777).uid(0o765).gid(0o24).size(0o11).mtime(0o13521657412).build();
+	header.uname = Some("keziah".into());
+	header.magic = Some(*b"ustar\0");
+	header.devmajor = Some(8);
+	
+	header.reset();
+	assert_eq!(header, Header::builder("").build());
+	assert!(header.is_regular_file());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "tar-interop")]
+
+use basic_tar::{ BasicTarError, Header, raw::{ self, TypeFlag } };
+use std::convert::TryFrom;
+
+
+#[test]
+fn test_from_tar() {
+	// Build a header with the tar crate
+	let mut tar_header = tar::Header::new_gnu();
+	tar_header.set_path("predefined_0.plain").unwrap();
+	tar_header.set_size(9);
+	tar_header.set_mode(0o644);
+	tar_header.set_uid(0o765);
+	tar_header.set_gid(0o24);
+	tar_header.set_mtime(0o13521657412);
+	tar_header.set_entry_type(tar::EntryType::Regular);
+	tar_header.set_cksum();
+	
+	let header = Header::try_from(&tar_header).unwrap();
+	assert_eq!(header.path, "predefined_0.plain");
+	assert_eq!(header.size, 9);
+	assert_eq!(header.mode, Some(0o644));
+	assert_eq!((header.uid, header.gid), (Some(0o765), Some(0o24)));
+	assert_eq!(header.mtime, Some(0o13521657412));
+	assert_eq!(header.typeflag, TypeFlag::REGULAR);
+	assert_eq!(header.linkname, None);
+	
+	// Corrupt fields are reported instead of being treated as empty
+	tar_header.as_old_mut().mode.copy_from_slice(b"0000z44\0");
+	tar_header.set_cksum();
+	assert_eq!(
+		Header::try_from(&tar_header),
+		Err(BasicTarError::InvalidData("Invalid octal number"))
+	);
+}
+
+
+#[test]
+fn test_into_tar() {
+	let header = Header::builder("predefined")
+		.mode(0o755).uid(0o765).gid(0o24).mtime(0o13521657412)
+		.typeflag(TypeFlag::DIRECTORY).build();
+	
+	let tar_header = tar::Header::try_from(&header).unwrap();
+	assert_eq!(tar_header.path().unwrap().to_str(), Some("predefined"));
+	assert_eq!(tar_header.mode().unwrap(), 0o755);
+	assert_eq!(tar_header.mtime().unwrap(), 0o13521657412);
+	assert_eq!(tar_header.entry_type(), tar::EntryType::Directory);
+	assert_eq!(tar_header.cksum().unwrap() as u64, raw::checksum(tar_header.as_bytes()));
+	assert_eq!(Header::try_from(&tar_header), Ok(header));
+	
+	// A header that cannot be serialized cannot be converted
+	let invalid = Header::builder("x".repeat(300)).build();
+	assert!(tar::Header::try_from(&invalid).is_err());
+}
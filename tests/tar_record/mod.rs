@@ -15,7 +15,7 @@ pub fn read_next(mut stream: impl Read) -> Result<(Header, Vec<u8>), Box<dyn Err
 	stream.read_exact(&mut header_raw)?;
 	
 	// Parse the header and get the payload lengths
-	let header = Header::parse(header_raw)?;
+	let header = Header::parse(&header_raw)?;
 	let payload_len = header.size;
 	let payload_total_len = payload_len.ceil_to_multiple_of(BLOCK_LEN as u64);
 	
@@ -0,0 +1,27 @@
+#![cfg(feature = "std")]
+
+use basic_tar::{ BasicTarError, Header };
+use std::time::{ Duration, UNIX_EPOCH };
+
+
+#[test]
+fn test_mtime_system_time() {
+	let time = UNIX_EPOCH + Duration::from_secs(0o13521657412);
+	
+	let mut header = Header::builder("predefined_0.plain").build();
+	assert_eq!(header.mtime_as_system_time(), None);
+	
+	header.set_mtime_from_system_time(time + Duration::from_millis(767)).unwrap();
+	assert_eq!(header.mtime, Some(0o13521657412));
+	assert_eq!(header.mtime_as_system_time(), Some(time));
+}
+
+
+#[test]
+fn test_mtime_before_epoch() {
+	let mut header = Header::builder("predefined_0.plain").build();
+	assert_eq!(
+		header.set_mtime_from_system_time(UNIX_EPOCH - Duration::from_secs(1)),
+		Err(BasicTarError::InvalidData("Modification time is before the UNIX epoch"))
+	);
+}
@@ -0,0 +1,382 @@
+use basic_tar::{
+	BasicTarError, Header,
+	raw::{ self, TypeFlag, BLOCK_LEN }
+};
+
+
+/// Creates a USTAR header for `path`
+fn ustar_header(path: &str) -> Header {
+	Header {
+		path: path.into(),
+		mode: Some(0o644), uid: Some(0o765), gid: Some(0o24),
+		size: 0o11, mtime: Some(0o13521657412),
+		typeflag: TypeFlag::REGULAR, linkname: None,
+		uname: Some("keziah".into()), gname: Some("staff".into()),
+		magic: Some(*b"ustar\0"), version: Some(*b"00"),
+		..Default::default()
+	}
+}
+
+
+#[test]
+fn test_owner_names() {
+	let header = ustar_header("predefined_0.plain");
+	let raw = header.serialize().unwrap();
+	assert_eq!(&raw[265..297], b"keziah\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
+	assert_eq!(&raw[297..329], b"staff\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
+	
+	let parsed = Header::parse(&raw).unwrap();
+	assert_eq!(parsed, header);
+	assert_eq!(parsed.serialize().unwrap().as_ref(), raw.as_ref());
+}
+
+
+#[test]
+fn test_prefix() {
+	// Create a path that needs to be split
+	let dir = "directory/".repeat(12);
+	let path = format!("{}{}", dir, "predefined_0.plain");
+	let header = ustar_header(&path);
+	
+	// Serialize the header and verify the split
+	let raw = header.serialize().unwrap();
+	assert_eq!(&raw[345..345 + dir.len() - 1], dir.trim_end_matches('/').as_bytes());
+	assert_eq!(&raw[..18], b"predefined_0.plain");
+	assert_eq!(Header::parse(&raw).unwrap(), header);
+}
+
+
+#[test]
+fn test_prefix_unsplittable() {
+	let header = ustar_header(&"a".repeat(101));
+	assert_eq!(
+		header.serialize().unwrap_err(),
+		BasicTarError::Unsupported("Path too long for ustar prefix/name split")
+	);
+}
+
+
+#[test]
+fn test_prefix_magic() {
+	let dir = "directory/".repeat(12);
+	let path = format!("{}{}", dir, "predefined_0.plain");
+	
+	// Classic headers without magic are upgraded to USTAR if they need a prefix
+	let classic = Header::builder(path.as_str()).build();
+	let raw = classic.serialize().unwrap();
+	assert_eq!((&raw[257..263], &raw[263..265]), (&b"ustar\0"[..], &b"00"[..]));
+	assert_eq!(Header::parse(&raw).unwrap().path, path);
+	
+	// ... and stay classic otherwise
+	let raw = Header::builder("predefined_0.plain").build().serialize().unwrap();
+	assert!(raw[257..265].iter().all(|b| *b == 0));
+	
+	// Headers with a foreign magic have no prefix
+	let foreign = Header{ magic: Some(*b"other\0"), version: Some(*b"00"), ..classic };
+	assert_eq!(
+		foreign.serialize().unwrap_err(),
+		BasicTarError::Unsupported("Path too long for name field")
+	);
+	
+	// The prefix field of headers with a foreign magic is not part of the path
+	let mut raw = ustar_header(&path).serialize().unwrap();
+	raw[257..263].copy_from_slice(b"other\0");
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw).unwrap().path, "predefined_0.plain");
+	assert_eq!(Header::parse_raw_fields(&raw).unwrap().0, b"predefined_0.plain");
+}
+
+
+#[test]
+fn test_raw_layout() {
+	// Create a raw header with distinct bytes
+	let mut raw = raw::header::raw();
+	raw.iter_mut().enumerate().for_each(|(idx, byte)| *byte = idx as u8);
+	
+	// Verify the field offsets and the round-trip
+	let tar = raw::header::Header::from(raw);
+	assert_eq!(&tar.name[..], &raw[..100]);
+	assert_eq!(&tar.typeflag[..], &raw[156..157]);
+	assert_eq!(&tar.magic[..], &raw[257..263]);
+	assert_eq!(&tar.prefix[..], &raw[345..500]);
+	assert_eq!(&raw::header::Raw::from(tar)[..], &raw[..]);
+}
+
+
+#[test]
+fn test_checksum() {
+	let mut raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	assert_eq!(raw::checksum(&raw), raw::stored_checksum(&raw).unwrap());
+	
+	// Corrupt the header
+	raw[0] = b'P';
+	assert_eq!(raw::checksum(&raw), raw::stored_checksum(&raw).unwrap() - (b'p' - b'P') as u64);
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Invalid header checksum")));
+}
+
+
+#[test]
+fn test_signed_checksum() {
+	// Create a header with a high-bit byte in the padding
+	let mut raw = raw::header::raw();
+	raw[..18].copy_from_slice(b"predefined_0.plain");
+	raw[124..136].copy_from_slice(b"00000000011\0");
+	raw[156] = TypeFlag::REGULAR;
+	raw[500] = 0xe4;
+	
+	// Compute and store the signed checksum
+	let signed = raw.iter().enumerate()
+		.map(|(idx, byte)| if (148..156).contains(&idx) { b' ' } else { *byte })
+		.fold(0i64, |sum, byte| sum + (byte as i8 as i64));
+	assert_ne!(signed as u64, raw::checksum(&raw));
+	raw[148..156].copy_from_slice(format!("{:06o}\0 ", signed).as_bytes());
+	
+	let header = Header::parse(&raw).unwrap();
+	assert_eq!(header.path, "predefined_0.plain");
+}
+
+
+#[test]
+fn test_parse_slice() {
+	let archive = include_bytes!("predefined_bsd.tar");
+	let header = Header::parse_slice(&archive[BLOCK_LEN * 2..BLOCK_LEN * 3]).unwrap();
+	assert_eq!(header.path, "PaxHeader/predefined_0.plain");
+	
+	assert_eq!(
+		Header::parse_slice(&archive[..BLOCK_LEN - 1]),
+		Err(BasicTarError::ApiMisuse("`data` must be exactly one block long"))
+	);
+}
+
+
+#[test]
+fn test_raw_accessors() {
+	let raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	let tar = raw::header::Header::from(raw);
+	assert_eq!(tar.typeflag_byte(), TypeFlag::REGULAR);
+	assert_eq!(tar.size_octal(), Ok(0o11));
+	assert_eq!(tar.name_bytes(), b"predefined_0.plain");
+	assert_eq!(tar.name_len(), 18);
+	
+	// A full-length name has no NUL terminator and is taken whole
+	let raw = ustar_header(&"x".repeat(100)).serialize().unwrap();
+	let tar = raw::header::Header::from(raw);
+	assert_eq!(tar.name_bytes(), "x".repeat(100).as_bytes());
+	assert_eq!(tar.name_len(), 100);
+	assert_eq!(Header::parse(&raw).unwrap().path, "x".repeat(100));
+}
+
+
+#[test]
+fn test_extra_bytes() {
+	let mut raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	raw[345..357].copy_from_slice(b"vendor field");
+	
+	let extra = raw::header::Header::from(raw).extra_bytes();
+	assert_eq!(&extra[..], &raw[257..500]);
+	assert_eq!(&extra[..6], b"ustar\0");
+	assert_eq!(&extra[88..100], b"vendor field");
+}
+
+
+#[test]
+fn test_serialize_into() {
+	let header = ustar_header("predefined_0.plain");
+	let mut raw = [0xff; BLOCK_LEN];
+	header.serialize_into(&mut raw).unwrap();
+	assert_eq!(&raw[..], &header.serialize().unwrap()[..]);
+	
+	// A failed serialization must not touch the block
+	let invalid = ustar_header(&"x".repeat(300));
+	assert!(invalid.serialize_into(&mut raw).is_err());
+	assert_eq!(&raw[..], &header.serialize().unwrap()[..]);
+}
+
+
+#[test]
+fn test_checksum_too_wide() {
+	let valid = ustar_header("predefined_0.plain").serialize().unwrap();
+	
+	// A checksum with seven significant digits must be rejected explicitly
+	let mut raw = valid;
+	let checksum = raw::checksum(&raw) + 0o1000000;
+	raw[148..156].copy_from_slice(format!("{:07o}\0", checksum).as_bytes());
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::InvalidData("Header checksum is too large"))
+	);
+	
+	// A base-256 checksum must be rejected too
+	let mut raw = valid;
+	raw[148..156].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::InvalidData("Header checksum is not octal"))
+	);
+	
+	// Leading zeroes are not significant
+	let mut raw = valid;
+	let checksum = raw::checksum(&raw);
+	raw[148..156].copy_from_slice(format!("{:07o}\0", checksum).as_bytes());
+	assert!(Header::parse(&raw).is_ok());
+}
+
+
+#[test]
+fn test_checksum_terminators() {
+	let valid = ustar_header("predefined_0.plain").serialize().unwrap();
+	let checksum = raw::checksum(&valid);
+	
+	// Accept both terminator orders, padding spaces and trailing garbage
+	let layouts = [
+		format!("{:06o}\0 ", checksum), format!("{:06o} \0", checksum),
+		format!("{:<6o}\0 ", checksum), format!("{:>6o} \0", checksum),
+		format!("{:06o}\0x", checksum)
+	];
+	for field in layouts.iter() {
+		let mut raw = valid;
+		raw[148..156].copy_from_slice(field.as_bytes());
+		assert_eq!(raw::stored_checksum(&raw), Ok(checksum), "{:?}", field);
+		assert!(Header::parse(&raw).is_ok(), "{:?}", field);
+	}
+	
+	// An empty checksum field is rejected
+	let mut raw = valid;
+	raw[148..156].copy_from_slice(b"      \0 ");
+	assert_eq!(
+		Header::parse(&raw),
+		Err(BasicTarError::InvalidData("Header checksum is not octal"))
+	);
+}
+
+
+#[test]
+fn test_parse_ignore_checksum() {
+	let header = ustar_header("predefined_0.plain");
+	let mut raw = header.serialize().unwrap();
+	raw[148..156].copy_from_slice(b"000000\0 ");
+	
+	// Only the checksum-tolerant parse accepts the corrupted checksum
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::InvalidData("Invalid header checksum")));
+	assert_eq!(Header::parse_ignore_checksum(&raw), Ok(header));
+	
+	// Empty headers and invalid fields are still rejected
+	assert_eq!(
+		Header::parse_ignore_checksum(&raw::header::raw()),
+		Err(BasicTarError::EmptyHeader)
+	);
+	raw[124] = b'8';
+	assert_eq!(
+		Header::parse_ignore_checksum(&raw),
+		Err(BasicTarError::InvalidData("Invalid octal number"))
+	);
+}
+
+
+#[test]
+fn test_parse_arbitrary() {
+	// Generate pseudo-random blocks with a simple xorshift generator
+	let mut state = 0x2545_f491_4f6c_dd1du64;
+	let mut next = || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+	
+	for round in 0..10_000 {
+		let mut raw = raw::header::raw();
+		raw.iter_mut().for_each(|byte| *byte = next() as u8);
+		
+		// Fix the checksum every other round to exercise the field decoding too
+		if round % 2 == 0 {
+			let checksum = raw::checksum(&raw);
+			raw[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+		}
+		let _ = Header::parse(&raw);
+	}
+}
+
+
+#[test]
+fn test_field_too_small() {
+	let mut header = ustar_header("predefined_0.plain");
+	header.linkname = Some("x".repeat(101));
+	assert_eq!(
+		header.serialize().unwrap_err(),
+		BasicTarError::FieldTooSmall{ needed: 101, available: 100 }
+	);
+}
+
+
+#[test]
+fn test_parse_lossy() {
+	// Create a header with a Latin-1 name
+	let mut raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	raw[..12].copy_from_slice(b"caf\xe9.plain\0\0");
+	let checksum = raw::checksum(&raw);
+	raw[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+	
+	assert_eq!(Header::parse(&raw), Err(BasicTarError::Unsupported("Header field is not UTF-8")));
+	assert_eq!(Header::parse_lossy(&raw).unwrap().path, "caf\u{fffd}.plain");
+}
+
+
+#[test]
+fn test_parse_raw_fields() {
+	// Create a header with a non-UTF-8 prefix, name and link name
+	let mut raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	raw[..12].copy_from_slice(b"caf\xe9.plain\0\0");
+	raw[157..161].copy_from_slice(b"\xff\xfe\0\0");
+	raw[345..350].copy_from_slice(b"dir\x80\0");
+	let checksum = raw::checksum(&raw);
+	raw[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+	
+	let (path, linkname) = Header::parse_raw_fields(&raw).unwrap();
+	assert_eq!(path, b"dir\x80/caf\xe9.plain");
+	assert_eq!(linkname, Some(b"\xff\xfe".to_vec()));
+	
+	// A regular header has no link name
+	let raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	assert_eq!(Header::parse_raw_fields(&raw), Ok((b"predefined_0.plain".to_vec(), None)));
+}
+
+
+#[test]
+fn test_eq_ignoring_checksum() {
+	let raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	let tar = raw::header::Header::from(raw);
+	
+	// A different checksum is ignored
+	let mut other = tar;
+	other.checksum = *b"000000\0 ";
+	assert!(tar.eq_ignoring_checksum(&other));
+	
+	// A different field is not
+	other.typeflag = [TypeFlag::DIRECTORY];
+	assert!(!tar.eq_ignoring_checksum(&other));
+}
+
+
+#[test]
+fn test_rewrite_checksum() {
+	let mut raw = ustar_header("predefined_0.plain").serialize().unwrap();
+	
+	// Rename the record in place and repair the checksum
+	raw[..18].copy_from_slice(b"predefined_1.plain");
+	assert!(Header::parse(&raw).is_err());
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(Header::parse(&raw).unwrap(), ustar_header("predefined_1.plain"));
+}
+
+
+#[test]
+fn test_serialize_unchecksummed() {
+	let header = ustar_header("predefined_0.plain");
+	let mut raw = header.serialize_unchecksummed().unwrap();
+	assert_eq!(&raw[148..156], b"        ");
+	
+	// Write the checksum in a second phase
+	raw::rewrite_checksum(&mut raw);
+	assert_eq!(&raw[..], &header.serialize().unwrap()[..]);
+}
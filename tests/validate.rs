@@ -0,0 +1,82 @@
+use basic_tar::{ BasicTarError, Header };
+
+
+#[test]
+fn test_validate() {
+	let header = Header::builder("predefined_0.plain").mode(0o644).size(0o11).build();
+	assert_eq!(header.validate(), Ok(()));
+	
+	let header = Header::builder("").build();
+	assert_eq!(header.validate(), Err(BasicTarError::InvalidData("Path is empty")));
+	
+	let header = Header::builder("a".repeat(101)).build();
+	assert_eq!(
+		header.validate(),
+		Err(BasicTarError::Unsupported("Path too long for ustar prefix/name split"))
+	);
+	
+	let header = Header::builder("link").linkname("a".repeat(101)).build();
+	assert_eq!(header.validate(), Err(BasicTarError::Unsupported("Link name is too long")));
+	
+	let header = Header::builder("large.bin").uid(u64::MAX).build();
+	assert_eq!(header.validate(), Err(BasicTarError::Unsupported("UID is too large")));
+	
+	let header = Header::builder("unknown").typeflag(b'?').build();
+	assert_eq!(header.validate(), Err(BasicTarError::Unsupported("Unknown type flag")));
+}
+
+
+#[test]
+fn test_validate_path() {
+	let safe = ["predefined_0.plain", "dir/predefined_0.plain", "./dir/", "dir/..file", "ab:c"];
+	for path in safe.iter() {
+		assert!(Header::builder(*path).build().is_path_safe(), "{}", path);
+	}
+	
+	let absolute = BasicTarError::InvalidData("Path is absolute");
+	let parent = BasicTarError::InvalidData("Path contains a parent directory component");
+	let unsafe_paths = [
+		("/etc/passwd", absolute), ("\\\\server\\share", absolute), ("C:\\Windows", absolute),
+		("../etc/passwd", parent), ("dir/../../etc", parent), ("dir\\..\\etc", parent),
+		("dir/..", parent), ("file\0.plain", BasicTarError::InvalidData("Path contains NUL bytes"))
+	];
+	for (path, error) in unsafe_paths.iter() {
+		let header = Header::builder(*path).build();
+		assert!(!header.is_path_safe(), "{}", path);
+		assert_eq!(header.validate_path(), Err(*error));
+	}
+}
+
+
+#[test]
+fn test_canonical_path() {
+	let paths = [
+		("./predefined_0.plain", "predefined_0.plain"), ("./dir//./file", "dir/file"),
+		("dir/", "dir/"), ("./", "."), ("/etc//passwd", "/etc/passwd"),
+		("dir/../file", "dir/../file")
+	];
+	for (path, canonical) in paths.iter() {
+		let header = Header::builder(*path).build();
+		assert_eq!(header.canonical_path(), *canonical);
+		assert_eq!(header.path, *path);
+	}
+}
+
+
+#[test]
+fn test_set_path() {
+	let mut header = Header::builder("predefined_0.plain").build();
+	
+	// A path that can be split into a prefix and a name is accepted
+	let long = format!("{}/{}", "d".repeat(150), "f".repeat(100));
+	header.set_path(long.as_str()).unwrap();
+	assert_eq!(header.path, long);
+	
+	// Unrepresentable paths are rejected immediately and leave the path unchanged
+	assert_eq!(header.set_path(""), Err(BasicTarError::InvalidData("Path is empty")));
+	assert_eq!(
+		header.set_path("f".repeat(101)),
+		Err(BasicTarError::Unsupported("Path too long for ustar prefix/name split"))
+	);
+	assert_eq!(header.path, long);
+}